@@ -6,12 +6,24 @@ use systemstat::Duration;
 use std::error::Error;
 use std::sync::{Arc, Mutex};
 
-use crate::display::state::UIState;
+use crate::display::state::{Tab, UIState};
 use crate::display::ui::{draw, init_ui, shutdown_ui};
 
-use crate::monitoring::polling::{SystemPollResult, SystemPoller, SystemPollerTarget};
+use crate::monitoring::energy::EnergyRates;
+use crate::monitoring::filesystem;
+use crate::monitoring::history::PollHistory;
+use crate::monitoring::polling::{SystemPoller, SystemPollerTarget};
 use crate::monitoring::system::SystemData;
-use crate::ringbuffer::RingBuffer;
+
+/// Number of past readings kept for history graphs.
+const POLL_HISTORY_LENGTH: usize = 120;
+
+/// Interval in seconds at which mounted filesystems are read again.
+const DISK_REFRESH_INTERVAL: f32 = 10.0;
+
+/// Seconds to wait for mounted filesystems to be read, before keeping the
+/// previous list until the next refresh.
+const DISK_READ_TIMEOUT: f32 = 5.0;
 
 enum MFAMessage {
     Exit,
@@ -20,6 +32,7 @@ enum MFAMessage {
 pub struct MainFrameApp {
     refresh_rate: f32,
     poll_rate: f32,
+    energy_rates: EnergyRates,
 }
 
 impl Default for MainFrameApp {
@@ -49,6 +62,14 @@ impl MainFrameApp {
         self
     }
 
+    /// Set the electricity price, and carbon intensity used to estimate the
+    /// cost, and emissions of the energy used during the session.
+    pub fn with_energy_rates(mut self, rates: EnergyRates) -> Self {
+        self.energy_rates = rates;
+
+        self
+    }
+
     /// Intstantiate a new app instance.
     ///
     /// A new instance of mainframe app has not acquired any resources, nor
@@ -59,6 +80,7 @@ impl MainFrameApp {
         MainFrameApp {
             refresh_rate: 20.0,
             poll_rate: 1.0,
+            energy_rates: EnergyRates::default(),
         }
     }
 
@@ -76,21 +98,38 @@ impl MainFrameApp {
         // --- Init sync primitives --- //
         // ui state
         let app_state = UIState::new_shared();
+        app_state.lock().unwrap().home_tab.energy_rates = self.energy_rates;
         let app_data = Arc::new(Mutex::new(SystemData::new_from_poll()));
 
-        let poll_results = Arc::new(Mutex::new(RingBuffer::<SystemPollResult>::new(1)));
+        let poll_results = Arc::new(Mutex::new(PollHistory::new(POLL_HISTORY_LENGTH)));
 
         let mut system_poller = SystemPoller::new().with_poll_targets(vec![
             SystemPollerTarget::CpuUsage,
+            SystemPollerTarget::CpuTimes,
             SystemPollerTarget::CpuTemperature,
+            SystemPollerTarget::CpuPower,
+            SystemPollerTarget::CpuFrequency,
             SystemPollerTarget::Gpu,
             SystemPollerTarget::Memory,
+            SystemPollerTarget::Cgroup,
+            SystemPollerTarget::CgroupTree,
+            SystemPollerTarget::Processes,
+            SystemPollerTarget::Network,
+            SystemPollerTarget::DiskIo,
+            SystemPollerTarget::Activity,
+            SystemPollerTarget::Pressure,
+            SystemPollerTarget::Battery,
+            SystemPollerTarget::Sensors,
         ]);
 
+        // The ui starts on the home tab, so the cgroup tree is not walked.
+        system_poller.set_cgroup_tree_enabled(false);
         poll_results.lock().unwrap().add(system_poller.poll());
 
         let _app_state_handle = app_state.clone();
+        let _app_state_handle_poll_thread = app_state.clone();
         let _app_data_handle = app_data.clone();
+        let _app_data_handle_disk_thread = app_data.clone();
         let _poll_result_handle_poll_thread = poll_results.clone();
         let _poll_result_handle_draw_thread = poll_results.clone();
 
@@ -103,6 +142,9 @@ impl MainFrameApp {
         let mut polling_interval =
             tokio::time::interval(Duration::from_secs_f32(1.0 / self.poll_rate));
 
+        let mut disk_interval =
+            tokio::time::interval(Duration::from_secs_f32(DISK_REFRESH_INTERVAL));
+
         // Launch ui thread
         let ui_thread = tokio::spawn(async move {
             loop {
@@ -118,12 +160,9 @@ impl MainFrameApp {
                     }
                 };
 
-                match msg {
-                    Some(MFAMessage::Exit) => {
-                        return;
-                    }
-                    _ => (),
-                };
+                if let Some(MFAMessage::Exit) = msg {
+                    return;
+                }
 
                 {
                     let mut s = _app_state_handle.lock().unwrap();
@@ -141,6 +180,20 @@ impl MainFrameApp {
             loop {
                 polling_interval.tick().await;
 
+                // The cgroup tree is only walked while the cgroups tab is
+                // open, and pressure is only polled for the cgroup selected
+                // in it.
+                let (cgroups_tab_open, selected_cgroup) = {
+                    let s = _app_state_handle_poll_thread.lock().unwrap();
+
+                    (
+                        s.current_tab == Tab::Cgroups,
+                        s.cgroups_tab.selected_path.clone(),
+                    )
+                };
+                system_poller.set_cgroup_tree_enabled(cgroups_tab_open);
+                system_poller.set_selected_cgroup(selected_cgroup);
+
                 let poll_result = system_poller.poll();
 
                 {
@@ -151,6 +204,31 @@ impl MainFrameApp {
             }
         });
 
+        // Launch disk refresh thread. Filesystems change rarely, and reading
+        // them can block on slow network mounts, so they are read on a
+        // blocking thread, with a timeout.
+        let disk_thread = tokio::spawn(async move {
+            let mut pending = None;
+
+            loop {
+                disk_interval.tick().await;
+
+                // A read which is stuck on an unresponsive mount is waited on
+                // again, rather than starting another one.
+                let mut read = pending
+                    .take()
+                    .unwrap_or_else(|| tokio::task::spawn_blocking(filesystem::read_disks));
+
+                let timeout = Duration::from_secs_f32(DISK_READ_TIMEOUT);
+
+                match tokio::time::timeout(timeout, &mut read).await {
+                    Ok(Ok(disks)) => _app_data_handle_disk_thread.lock().unwrap().disks = disks,
+                    Ok(Err(_)) => (),
+                    Err(_) => pending = Some(read),
+                }
+            }
+        });
+
         let mut events = EventStream::new();
 
         // Run main processing loop
@@ -158,22 +236,27 @@ impl MainFrameApp {
             // Consume pending events
             match events.next().await {
                 Some(Ok(Event::Key(evnt))) => match evnt.code {
-                    // Quit key
-                    KeyCode::Char('q') => {
+                    // Quit key, unless a popup is open, which gets every
+                    // key first.
+                    KeyCode::Char('q') if app_state.lock().unwrap().popup.is_none() => {
                         ui_tx.send(MFAMessage::Exit).unwrap();
                         break 'mainloop;
                     }
-                    // Tab selection keys
-                    // KeyCode::Char('h') => {
-                    //     app_state.lock().unwrap().current_tab = 0;
-                    // }
-                    // KeyCode::Char('u') => {
-                    //     app_state.lock().unwrap().current_tab = 1;
-                    // }
-                    // KeyCode::Char('d') => {
-                    //     app_state.lock().unwrap().current_tab = 2;
-                    // }
-                    _ => (),
+                    // Popup, tab selection, and per-tab keys
+                    _ => {
+                        let mut state = app_state.lock().unwrap();
+
+                        if let Some(action) = state.handle_key(evnt) {
+                            if let Err(e) = action.execute() {
+                                state.show_error(format!("{} failed: {}", action.describe(), e));
+                            }
+                        }
+
+                        if std::mem::take(&mut state.home_tab.reset_energy) {
+                            state.home_tab.energy_baseline =
+                                poll_results.lock().unwrap().latest().energy;
+                        }
+                    }
                 },
                 Some(Err(e)) => return Err(Box::new(e)),
                 None => break 'mainloop,
@@ -183,6 +266,7 @@ impl MainFrameApp {
 
         ui_thread.abort();
         polling_thread.abort();
+        disk_thread.abort();
 
         Ok(())
     }
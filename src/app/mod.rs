@@ -1,3 +1,4 @@
 /// Module app provides centralized logic for running the mainframe cli app.
+#[allow(clippy::module_inception)]
 mod app;
 pub use app::MainFrameApp;
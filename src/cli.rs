@@ -1,11 +1,12 @@
-use clap::{arg, Parser};
+use clap::Parser;
 
 static ABOUT_STRING: &str = "
 A fast and lightweight visual system monitor.
 Reports live data about cpu and gpu usage, temperature, memory consumption, 
 and more.
 
-To configure poll rate, and refresh rate, see the available option flags below.";
+To configure poll rate, refresh rate, and the rates used to estimate the cost
+and emissions of the energy used, see the available option flags below.";
 
 #[derive(Parser, Debug)]
 #[command(author = "mlafrance")]
@@ -22,4 +23,16 @@ pub struct MainframeOpts {
         help = "Set the refresh rate in hz."
     )]
     pub refresh_rate: f32,
+
+    #[arg(
+        long,
+        help = "Set the electricity price per kWh, to estimate energy cost."
+    )]
+    pub price_per_kwh: Option<f64>,
+
+    #[arg(
+        long,
+        help = "Set the carbon intensity of electricity in grams of CO2 per kWh, to estimate emissions."
+    )]
+    pub co2_per_kwh: Option<f64>,
 }
@@ -23,12 +23,12 @@ static BAR_CHARACTER: char = '|';
 ///
 /// - name: A label which will sit to the left of the chart.
 /// - value: The current value of the bar chart. The percentage fill of the
-/// chart will be calculated from this value, and the bounds.
+///   chart will be calculated from this value, and the bounds.
 /// - bounds: The expected upper and lower bounds for the data (the bottom and
-/// top of the bar chart)
+///   top of the bar chart)
 /// - name_width: How much to pad the name to.
 /// - width: How many characters the full bar chart (including padded name)
-/// should be calculated to take up.
+///   should be calculated to take up.
 ///
 /// # Example
 /// ```
@@ -72,9 +72,7 @@ pub fn generate_bar_chart(
     // Number of text characters the actual bar characters will occupy.
     let bar_characters = width as i32 - 2 - name_width as i32;
 
-    // yes this will panic if bounds[1] is 0, but as this is an internal api,
-    // not gonna worry about it.
-    let blocks_f = ((value - bounds.0) / bounds.1) * bar_characters as f32;
+    let blocks_f = ((value - bounds.0) / (bounds.1 - bounds.0)) * bar_characters as f32;
 
     let blocks = blocks_f.round() as i32;
 
@@ -90,8 +88,11 @@ pub fn generate_bar_chart(
 
     spans.push(start);
 
+    // Each block is colored by the value at its position in the bounds, so
+    // that a full bar ends in the same color for any bounds.
     for i in 0..blocks {
-        let color = get_color_for_range(100.0 * i as f32 / bar_characters as f32, bounds);
+        let block_value = bounds.0 + (bounds.1 - bounds.0) * i as f32 / bar_characters as f32;
+        let color = get_color_for_range(block_value, bounds);
 
         spans.push(Span::styled(
             format!("{}", BAR_CHARACTER),
@@ -110,3 +111,104 @@ pub fn generate_bar_chart(
 
     Box::new(Line::from(spans))
 }
+
+/// Generates a text-only bar, divided into a colored segment for each part
+/// of a whole.
+///
+/// - segments: The size, and color of each part, from left to right.
+/// - total: The size of the whole. Space not covered by any segment is drawn
+///   as empty.
+/// - width: How many characters the bar should take up, including brackets.
+pub fn generate_stacked_bar(segments: &[(u64, Color)], total: u64, width: usize) -> Line<'static> {
+    let start = Span::styled("[", Style::new().gray());
+    let end = Span::styled("]", Style::new().gray());
+
+    let bar_characters = width.saturating_sub(2);
+    let total = total.max(1) as f64;
+
+    let mut spans = vec![start];
+    let mut drawn = 0usize;
+    let mut covered = 0u64;
+
+    // Segment boundaries are rounded from the running total, so that
+    // rounding errors do not add up across segments.
+    for (size, color) in segments {
+        covered += size;
+
+        let boundary = ((covered as f64 / total).min(1.0) * bar_characters as f64).round() as usize;
+
+        spans.push(Span::styled(
+            BAR_CHARACTER
+                .to_string()
+                .repeat(boundary.saturating_sub(drawn)),
+            Style::new().fg(*color),
+        ));
+
+        drawn = drawn.max(boundary);
+    }
+
+    spans.push(Span::styled(
+        BAR_CHARACTER.to_string().repeat(bar_characters - drawn),
+        Style::new().fg(Color::DarkGray),
+    ));
+    spans.push(end);
+
+    Line::from(spans)
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+
+    fn segment_widths(line: &Line) -> Vec<usize> {
+        line.spans
+            .iter()
+            .map(|s| s.content.chars().count())
+            .collect()
+    }
+
+    fn block_colors(line: &Line) -> Vec<Option<Color>> {
+        line.spans
+            .iter()
+            .filter(|s| s.content == BAR_CHARACTER.to_string())
+            .map(|s| s.style.fg)
+            .collect()
+    }
+
+    #[test]
+    fn test_bar_chart_colors_with_other_bounds() {
+        // A full bar over a byte range goes from green to red.
+        let bytes = (0f32, 8f32 * 1024f32 * 1024f32 * 1024f32);
+        let full = generate_bar_chart("", bytes.1, bytes, 0, 22);
+        let colors = block_colors(&full);
+
+        assert!(colors.len() == 20);
+        assert!(colors[0] == Some(Color::Green));
+        assert!(colors[13] == Some(Color::Yellow));
+        assert!(colors[19] == Some(Color::Red));
+
+        // A bar over a small range, such as a cpu quota, is not red while
+        // far from its limit.
+        let low = generate_bar_chart("", 0.5, (0f32, 2f32), 0, 22);
+        let colors = block_colors(&low);
+
+        assert!(colors[..5].iter().all(|c| *c == Some(Color::Green)));
+        assert!(colors[5..].iter().all(|c| *c == Some(Color::DarkGray)));
+
+        // Bounds which do not start at 0.
+        let offset = generate_bar_chart("", 20.0, (10f32, 20f32), 0, 22);
+        assert!(block_colors(&offset)[19] == Some(Color::Red));
+    }
+
+    #[test]
+    fn test_generate_stacked_bar() {
+        let line = generate_stacked_bar(&[(25, Color::Green), (50, Color::Yellow)], 100, 22);
+
+        // Brackets, two segments, and the empty remainder.
+        assert!(segment_widths(&line) == [1, 5, 10, 5, 1]);
+
+        // Segments which overflow the total are clamped.
+        let line = generate_stacked_bar(&[(80, Color::Green), (80, Color::Yellow)], 100, 12);
+        assert!(segment_widths(&line) == [1, 8, 2, 0, 1]);
+    }
+}
@@ -0,0 +1,133 @@
+// Contains functionality for drawing ui elements related to batteries, and
+// power supplies.
+use ratatui::{
+    layout::Rect,
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, BorderType, Borders, Padding, Paragraph},
+    Frame,
+};
+
+use crate::monitoring::battery::{Battery, BatteryPollResult, BatteryStatus};
+
+use super::{bar_chart::generate_stacked_bar, util::format_uptime};
+
+/// Lines taken up by each battery.
+const BATTERY_LINES: u16 = 3;
+
+/// Height the battery block needs to show every battery, or 0 if there are
+/// no batteries, in which case the block is hidden.
+pub fn battery_block_height(battery: &BatteryPollResult) -> u16 {
+    match battery.batteries.len() as u16 {
+        0 => 0,
+        n => n * BATTERY_LINES + 3,
+    }
+}
+
+/// Color of the charge bar, which turns yellow, and then red as the battery
+/// runs low.
+fn charge_color(capacity: f32) -> Color {
+    match capacity {
+        c if c <= 15f32 => Color::Red,
+        c if c <= 35f32 => Color::Yellow,
+        _ => Color::Green,
+    }
+}
+
+/// Build the lines of a single battery, with bars `width` characters wide.
+fn battery_lines(b: &Battery, width: usize) -> Vec<Line<'static>> {
+    let bold = |s: String| Span::styled(s, Style::new().add_modifier(Modifier::BOLD));
+
+    let remaining = match (b.status, b.time_remaining()) {
+        (BatteryStatus::Discharging, Some(t)) => format!("  {} to empty", format_uptime(t)),
+        (BatteryStatus::Charging, Some(t)) => format!("  {} to full", format_uptime(t)),
+        _ => String::new(),
+    };
+
+    let percent = format!(" {:.0}%", b.capacity);
+
+    let mut bar = generate_stacked_bar(
+        &[(b.capacity.round() as u64, charge_color(b.capacity))],
+        100,
+        width.saturating_sub(percent.len()),
+    );
+    bar.spans.push(Span::raw(percent));
+
+    let mut details = vec![];
+
+    if let Some(power) = b.power {
+        details.push(format!("Power: {:.1} W", power));
+    }
+
+    if let Some(health) = b.health() {
+        details.push(format!("Health: {:.0}%", health));
+    }
+
+    vec![
+        Line::from(vec![
+            bold(format!("{}  ", b.name)),
+            Span::raw(format!("{}{}", b.status.name(), remaining)),
+        ]),
+        bar,
+        Line::from(details.join("  ")),
+    ]
+}
+
+/// Draws a blocked element with the charge, charging state, power draw,
+/// time remaining, and health of each battery, and the AC adapter state.
+pub fn draw_battery_block(battery: &BatteryPollResult, f: &mut Frame, area: Rect) {
+    let block = Block::new()
+        .title(" Battery ")
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded)
+        .padding(Padding::new(2, 2, 0, 0));
+
+    let width = block.inner(area).width as usize;
+
+    let ac = match battery.ac_online {
+        Some(true) => "online",
+        Some(false) => "offline",
+        None => "none",
+    };
+
+    let mut lines = vec![Line::from(vec![
+        Span::styled("AC adapter: ", Style::new().add_modifier(Modifier::BOLD)),
+        Span::raw(ac),
+    ])];
+
+    for b in &battery.batteries {
+        lines.extend(battery_lines(b, width));
+    }
+
+    f.render_widget(Paragraph::new(lines).block(block), area)
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+
+    #[test]
+    fn test_battery_lines() {
+        let battery = Battery {
+            name: "BAT0".to_string(),
+            status: BatteryStatus::Discharging,
+            capacity: 54.0,
+            power: Some(12.5),
+            energy_now: Some(27.0),
+            energy_full: Some(50.0),
+            energy_full_design: Some(57.0),
+        };
+
+        let text: Vec<String> = battery_lines(&battery, 20)
+            .iter()
+            .map(|l| l.spans.iter().map(|s| s.content.as_ref()).collect())
+            .collect();
+
+        assert!(text.len() == BATTERY_LINES as usize);
+        assert!(text[0] == "BAT0  Discharging  2h 9m to empty");
+        assert!(text[1].chars().count() == 20 && text[1].ends_with("] 54%"));
+        assert!(text[2] == "Power: 12.5 W  Health: 88%");
+
+        assert!(battery_block_height(&BatteryPollResult::default()) == 0);
+    }
+}
@@ -0,0 +1,193 @@
+// Contains functionality for drawing ui elements related to the cgroup
+// hierarchy.
+use std::collections::HashSet;
+
+use human_bytes::human_bytes;
+use ratatui::{
+    layout::{Constraint, Direction, Layout, Rect},
+    style::{Color, Style, Stylize},
+    text::Span,
+    widgets::{
+        block::{Position, Title},
+        Block, BorderType, Borders, Cell, Paragraph, Row, Table, TableState,
+    },
+    Frame,
+};
+
+use crate::monitoring::{cgroup_tree::CgroupNode, pressure::PressurePollResult};
+
+use super::{
+    pressure::{draw_pressure_block, pressure_block_height},
+    state::CgroupsTabState,
+    util::{default_block, get_color_for_range},
+};
+
+/// Nodes which are not hidden below a collapsed ancestor, in the order they
+/// are given in, which must list parents before their children.
+fn visible_rows<'a>(nodes: &'a [CgroupNode], collapsed: &HashSet<String>) -> Vec<&'a CgroupNode> {
+    let mut folded_depth: Option<usize> = None;
+
+    nodes
+        .iter()
+        .filter(|n| {
+            if folded_depth.is_some_and(|depth| n.depth > depth) {
+                return false;
+            }
+
+            folded_depth = match n.has_children && collapsed.contains(&n.path) {
+                true => Some(n.depth),
+                false => None,
+            };
+
+            true
+        })
+        .collect()
+}
+
+/// Format an optional value, or a dash if it is not known.
+fn format_optional<T>(value: Option<T>, format: impl Fn(T) -> String) -> String {
+    value.map(format).unwrap_or_else(|| "-".to_string())
+}
+
+/// Draws the cgroups tab, which contains a collapsible tree of every cgroup,
+/// with its cpu, memory, io, and task usage, and the pressure of the
+/// selected cgroup below it.
+///
+/// The selected row, and scroll offset are written back to the given
+/// `state`, along with the path of the selected cgroup.
+pub fn draw_cgroups_tab(
+    nodes: &[CgroupNode],
+    pressure: &PressurePollResult,
+    state: &mut CgroupsTabState,
+    f: &mut Frame,
+    area: Rect,
+) {
+    if nodes.is_empty() {
+        f.render_widget(
+            Paragraph::new("No cgroup v2 hierarchy found.")
+                .dim()
+                .block(default_block(" Cgroups ")),
+            area,
+        );
+        return;
+    }
+
+    let rows = visible_rows(nodes, &state.collapsed);
+
+    state.selected = state.selected.min(rows.len().saturating_sub(1));
+    state.selected_path = rows.get(state.selected).map(|n| n.path.clone());
+
+    let l = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints(vec![
+            Constraint::Min(0),
+            Constraint::Length(pressure_block_height(pressure)),
+        ])
+        .split(area);
+
+    let (table_area, pressure_area) = (l[0], l[1]);
+
+    let header = Row::new(vec![
+        "Name", "Type", "CPU %", "Memory", "Read/s", "Write/s", "Tasks",
+    ])
+    .style(Style::new().bold())
+    .bottom_margin(1);
+
+    let widths = vec![
+        Constraint::Min(24),
+        Constraint::Length(26),
+        Constraint::Length(7),
+        Constraint::Length(10),
+        Constraint::Length(10),
+        Constraint::Length(10),
+        Constraint::Length(6),
+    ];
+
+    let table_rows = rows.iter().map(|n| {
+        let marker = match (n.has_children, state.collapsed.contains(&n.path)) {
+            (true, true) => "▸ ",
+            (true, false) => "▾ ",
+            (false, _) => "  ",
+        };
+
+        let cpu = Span::styled(
+            format_optional(n.cpu_usage, |c| format!("{:.1}", c)),
+            Style::new().fg(get_color_for_range(
+                n.cpu_usage.unwrap_or(0f32),
+                (0f32, 100f32),
+            )),
+        );
+
+        Row::new(vec![
+            Cell::from(format!("{}{}{}", "  ".repeat(n.depth), marker, n.name)),
+            Cell::from(n.description.clone().unwrap_or_default()).dim(),
+            Cell::from(cpu),
+            Cell::from(format_optional(n.memory_current, |m| human_bytes(m as f64))),
+            Cell::from(format_optional(n.io_read_per_sec, human_bytes)),
+            Cell::from(format_optional(n.io_write_per_sec, human_bytes)),
+            Cell::from(format_optional(n.pids_current, |p| p.to_string())),
+        ])
+    });
+
+    let table = Table::new(table_rows, widths)
+        .header(header)
+        .block(
+            Block::default()
+                .title(format!(" Cgroups ({}) ", nodes.len()))
+                .title(Title::from(" ↑/↓: Select  Space: Fold ").position(Position::Bottom))
+                .borders(Borders::ALL)
+                .border_type(BorderType::Rounded),
+        )
+        .highlight_style(Style::new().bg(Color::DarkGray).bold());
+
+    let mut table_state = TableState::default()
+        .with_offset(state.offset)
+        .with_selected(Some(state.selected));
+
+    f.render_stateful_widget(table, table_area, &mut table_state);
+
+    state.offset = table_state.offset();
+
+    let name = rows.get(state.selected).map(|n| n.name.as_str());
+    let title = format!(" Pressure of {} (10s / 60s / 300s) ", name.unwrap_or("/"));
+
+    draw_pressure_block(pressure, &title, f, pressure_area);
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+
+    #[test]
+    fn test_visible_rows() {
+        let node = |path: &str, depth: usize, has_children: bool| CgroupNode {
+            path: path.to_string(),
+            depth,
+            has_children,
+            ..Default::default()
+        };
+
+        let nodes = vec![
+            node("/", 0, true),
+            node("/a", 1, true),
+            node("/a/b", 2, true),
+            node("/a/b/c", 3, false),
+            node("/a/d", 2, false),
+            node("/e", 1, false),
+        ];
+
+        let paths = |collapsed: &[&str]| -> Vec<String> {
+            let collapsed = collapsed.iter().map(|p| p.to_string()).collect();
+
+            visible_rows(&nodes, &collapsed)
+                .into_iter()
+                .map(|n| n.path.clone())
+                .collect()
+        };
+
+        assert!(paths(&[]).len() == 6);
+        assert!(paths(&["/a"]) == ["/", "/a", "/e"]);
+        assert!(paths(&["/a/b", "/e"]) == ["/", "/a", "/a/b", "/a/d", "/e"]);
+        assert!(paths(&["/"]) == ["/"]);
+    }
+}
@@ -1,27 +1,155 @@
 // Contains functionality for drawing ui elements related to cpu reporting.
 use ratatui::{
     layout::{Constraint, Layout, Rect},
-    text::Line,
-    widgets::{Block, BorderType::Rounded, Borders, Padding, Paragraph},
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{
+        block::{Position, Title},
+        Block,
+        BorderType::Rounded,
+        Borders, Padding, Paragraph,
+    },
     Frame,
 };
 
-use crate::monitoring::polling::Measurement;
+use crate::monitoring::{
+    cgroup::{format_cpu_list, CgroupLimits},
+    cpufreq::CpuFrequency,
+    polling::{Measurement, SystemPollResult},
+    procstat::CpuTimeBreakdown,
+    rapl::{self, RaplDomainKind},
+    sensors::Sensor,
+};
+
+use super::bar_chart::{generate_bar_chart, generate_stacked_bar};
+
+/// Label, percentage, and color of a cpu state.
+type CpuTimeSegment = (&'static str, fn(&CpuTimeBreakdown) -> f32, Color);
+
+/// Cpu states shown in the stacked usage bars, from left to right. Idle time
+/// is left empty.
+const CPU_TIME_SEGMENTS: [CpuTimeSegment; 8] = [
+    ("user", |t| t.user, Color::Green),
+    ("nice", |t| t.nice, Color::Blue),
+    ("sys", |t| t.system, Color::Red),
+    ("iowait", |t| t.iowait, Color::Magenta),
+    ("irq", |t| t.irq, Color::Yellow),
+    ("softirq", |t| t.softirq, Color::LightYellow),
+    ("steal", |t| t.steal, Color::Cyan),
+    ("guest", |t| t.guest, Color::LightGreen),
+];
 
-use super::bar_chart::generate_bar_chart;
+/// Build a line with a bar for a single cpu, divided into a segment per cpu
+/// state, `width` characters wide including the cpu name.
+fn cpu_time_bar(times: &CpuTimeBreakdown, width: usize) -> Line<'static> {
+    // Percentages are scaled up, as the stacked bar takes whole numbers.
+    let segments: Vec<(u64, Color)> = CPU_TIME_SEGMENTS
+        .iter()
+        .map(|(_, percent, color)| ((percent(times) * 100f32).round() as u64, *color))
+        .collect();
+
+    let mut spans = vec![Span::raw(format!("{:<6}", times.name))];
+    spans.extend(generate_stacked_bar(&segments, 10000, width.saturating_sub(6)).spans);
+
+    Line::from(spans)
+}
 
 /// Draw the cpu usage block to the given frame.
 ///
 /// The CPU usage block is a scrollable block element that contains usage stats
-/// for current cpus.
-pub fn draw_cpu_usage_block(readings: &Vec<Measurement>, f: &mut Frame, area: Rect) {
+/// for current cpus. When a time breakdown is available, each cpu's bar is
+/// divided into the states its time was spent in, with a legend above.
+///
+/// If the cgroup mainframe runs in may use fewer cpus than the host has, the
+/// limit is shown in the title, along with a bar of the cgroup's usage
+/// against it.
+pub fn draw_cpu_usage_block(
+    readings: &[Measurement],
+    times: &[CpuTimeBreakdown],
+    cgroup: Option<&CgroupLimits>,
+    f: &mut Frame,
+    area: Rect,
+) {
+    let host_cpus = readings.len().max(times.len());
+    let limit = cgroup.and_then(|c| Some((c, c.cpu_limit(host_cpus)?)));
+
+    let title = match limit {
+        Some((c, cpus)) if c.cpuset_limited(host_cpus) => format!(
+            " CPU Usage ({} limit: {} cpus, cpuset {}) ",
+            c.version.name(),
+            cpus,
+            format_cpu_list(&c.cpuset)
+        ),
+        Some((c, cpus)) => format!(" CPU Usage ({} limit: {} cpus) ", c.version.name(), cpus),
+        None => " CPU Usage ".to_string(),
+    };
+
     let block = Block::default()
-        .title(" CPU Usage ")
+        .title(Span::styled(
+            title,
+            match limit {
+                Some(_) => Style::new().fg(Color::Yellow),
+                None => Style::new(),
+            },
+        ))
+        .title(Title::from(" f: Show frequency ").position(Position::Bottom))
         .borders(Borders::ALL)
         .padding(Padding::new(1, 1, 0, 0))
         .border_type(Rounded);
 
-    let inner_area = block.inner(area);
+    let mut inner_area = block.inner(area);
+
+    f.render_widget(block, area);
+
+    let mut header = vec![];
+
+    // The label is declared out here, as the bar borrows it.
+    let usage_label = limit.map(|(c, cpus)| {
+        let usage = c.cpu_usage.unwrap_or(0f32);
+
+        (
+            format!("cgroup {:.2}/{}", usage, cpus),
+            usage.min(cpus),
+            cpus,
+        )
+    });
+
+    if let Some((label, usage, cpus)) = &usage_label {
+        header.push(*generate_bar_chart(
+            label,
+            *usage,
+            (0f32, *cpus),
+            label.len() + 1,
+            inner_area.width as usize,
+        ));
+    }
+
+    if !times.is_empty() {
+        let legend: Vec<Span> = CPU_TIME_SEGMENTS
+            .iter()
+            .flat_map(|(label, _, color)| {
+                [
+                    Span::styled("■ ", Style::new().fg(*color)),
+                    Span::raw(format!("{}  ", label)),
+                ]
+            })
+            .collect();
+
+        header.push(Line::from(legend));
+    }
+
+    if !header.is_empty() {
+        let l = Layout::default()
+            .constraints(vec![
+                Constraint::Length(header.len() as u16),
+                Constraint::Min(0),
+            ])
+            .split(inner_area);
+
+        f.render_widget(Paragraph::new(header), l[0]);
+
+        inner_area = l[1];
+    }
 
     let left_right_layout = Layout::default()
         .direction(ratatui::layout::Direction::Horizontal)
@@ -34,27 +162,129 @@ pub fn draw_cpu_usage_block(readings: &Vec<Measurement>, f: &mut Frame, area: Re
 
     let (left_area, right_area) = (left_right_layout[0], left_right_layout[2]);
 
+    let mut paragraphs = vec![Vec::<Line>::new(); 2];
+
+    match (readings.len(), times.len()) {
+        (0, 0) => (),
+        (_, 0) => {
+            readings
+                .iter()
+                .enumerate()
+                .for_each(|(i, measurement): (usize, &Measurement)| {
+                    let bar_chart = *generate_bar_chart(
+                        &measurement.name,
+                        measurement.value,
+                        (0f32, 100f32),
+                        6,
+                        left_area.width.saturating_sub(2) as usize,
+                    );
+
+                    paragraphs[i % 2].push(bar_chart);
+                })
+        }
+        _ => times.iter().enumerate().for_each(|(i, t)| {
+            paragraphs[i % 2].push(cpu_time_bar(t, left_area.width.saturating_sub(2) as usize));
+        }),
+    };
+
+    f.render_widget(Paragraph::new(paragraphs[0].clone()), left_area);
+    f.render_widget(Paragraph::new(paragraphs[1].clone()), right_area);
+}
+
+/// Summarize a setting shared by every cpu, such as the scaling governor.
+///
+/// Returns the setting if every cpu agrees on it, `mixed` if they do not, or
+/// `n/a` if no cpu reports it.
+fn summarize<'a>(mut values: impl Iterator<Item = Option<&'a str>>) -> &'a str {
+    let Some(first) = values.next().flatten() else {
+        return "n/a";
+    };
+
+    match values.all(|v| v == Some(first)) {
+        true => first,
+        false => "mixed",
+    }
+}
+
+/// Draw the cpu frequency block to the given frame.
+///
+/// The CPU frequency block shows the scaling governor, energy performance
+/// preference, and frequency limits of the cpus, followed by the current
+/// frequency of each cpu, relative to the highest frequency it supports.
+pub fn draw_cpu_frequency_block(cpus: &[CpuFrequency], f: &mut Frame, area: Rect) {
+    let block = Block::default()
+        .title(" CPU Frequency ")
+        .title(Title::from(" f: Show usage ").position(Position::Bottom))
+        .borders(Borders::ALL)
+        .padding(Padding::new(1, 1, 0, 0))
+        .border_type(Rounded);
+
+    let inner_area = block.inner(area);
+
     f.render_widget(block, area);
 
+    if cpus.is_empty() {
+        f.render_widget(
+            Paragraph::new("No cpu frequency information found."),
+            inner_area,
+        );
+        return;
+    }
+
+    let l = Layout::default()
+        .constraints(vec![Constraint::Length(2), Constraint::Min(0)])
+        .split(inner_area);
+
+    let bold = |s: &'static str| Span::styled(s, Style::new().add_modifier(Modifier::BOLD));
+
+    let min = cpus.iter().map(|c| c.min).min().unwrap_or(0);
+    let max = cpus.iter().map(|c| c.max).max().unwrap_or(0);
+    let hardware_max = cpus.iter().map(|c| c.hardware_max).max().unwrap_or(0);
+
+    let summary = Line::from(vec![
+        bold("Governor: "),
+        Span::raw(summarize(cpus.iter().map(|c| c.governor.as_deref()))),
+        bold("  EPP: "),
+        Span::raw(summarize(
+            cpus.iter()
+                .map(|c| c.energy_performance_preference.as_deref()),
+        )),
+        bold("  Policy: "),
+        Span::raw(format!(
+            "{}-{} MHz (hardware max {} MHz)",
+            min, max, hardware_max
+        )),
+    ]);
+
+    f.render_widget(Paragraph::new(summary), l[0]);
+
+    let left_right_layout = Layout::default()
+        .direction(ratatui::layout::Direction::Horizontal)
+        .constraints(vec![
+            Constraint::Percentage(50),
+            Constraint::Length(1),
+            Constraint::Percentage(50),
+        ])
+        .split(l[1]);
+
+    let (left_area, right_area) = (left_right_layout[0], left_right_layout[2]);
+
+    let labels: Vec<String> = cpus
+        .iter()
+        .map(|c| format!("cpu{} {:.2}GHz", c.cpu, c.current as f32 / 1000f32))
+        .collect();
+
     let mut paragraphs = vec![Vec::<Line>::new(); 2];
 
-    match readings.len() {
-        0 => (),
-        _ => readings
-            .iter()
-            .enumerate()
-            .for_each(|(i, measurement): (usize, &Measurement)| {
-                let bar_chart = *generate_bar_chart(
-                    &measurement.name,
-                    measurement.value,
-                    (0f32, 100f32),
-                    6,
-                    left_area.width as usize - 2,
-                );
-
-                paragraphs[i % 2].push(bar_chart);
-            }),
-    };
+    for (i, (cpu, label)) in cpus.iter().zip(labels.iter()).enumerate() {
+        paragraphs[i % 2].push(*generate_bar_chart(
+            label,
+            cpu.current.min(cpu.hardware_max) as f32,
+            (0f32, cpu.hardware_max.max(1) as f32),
+            14,
+            left_area.width.saturating_sub(2) as usize,
+        ));
+    }
 
     f.render_widget(Paragraph::new(paragraphs[0].clone()), left_area);
     f.render_widget(Paragraph::new(paragraphs[1].clone()), right_area);
@@ -63,7 +293,7 @@ pub fn draw_cpu_usage_block(readings: &Vec<Measurement>, f: &mut Frame, area: Re
 /// Draws a blocked bar chart reporting cpu average usage.
 ///
 /// Average usage is calculated as the average over all currently polled cpus.
-pub fn draw_cpu_average_block(readings: &Vec<Measurement>, f: &mut Frame, area: Rect) {
+pub fn draw_cpu_average_block(readings: &[Measurement], f: &mut Frame, area: Rect) {
     let b = Block::default()
         .borders(Borders::ALL)
         .border_type(Rounded)
@@ -94,7 +324,10 @@ pub fn draw_cpu_average_block(readings: &Vec<Measurement>, f: &mut Frame, area:
 }
 
 /// Draws a blocked bar chart reporting cpu temperature.
-pub fn draw_cpu_temp_block(cpu_temp: &Measurement, f: &mut Frame, area: Rect) {
+///
+/// The bar is drawn up to the critical temperature reported by the sensor,
+/// or up to 100C if it reports none.
+pub fn draw_cpu_temp_block(cpu_temp: Option<&Sensor>, f: &mut Frame, area: Rect) {
     let b = Block::default()
         .borders(Borders::ALL)
         .border_type(Rounded)
@@ -104,12 +337,20 @@ pub fn draw_cpu_temp_block(cpu_temp: &Measurement, f: &mut Frame, area: Rect) {
 
     let width = inner_area.width;
 
-    let temp_text = format!("{}C", cpu_temp.value);
+    let (temp, upper_bound) = match cpu_temp {
+        Some(s) => (s.value as f32, s.upper_bound().unwrap_or(100f64) as f32),
+        None => (0f32, 100f32),
+    };
+
+    let temp_text = match cpu_temp {
+        Some(_) => format!("{}C", temp.round()),
+        None => "N/A".to_string(),
+    };
 
     let p = Paragraph::new(*generate_bar_chart(
         &temp_text,
-        cpu_temp.value,
-        (0f32, 100f32),
+        temp.min(upper_bound),
+        (0f32, upper_bound),
         8,
         width as usize,
     ));
@@ -117,3 +358,165 @@ pub fn draw_cpu_temp_block(cpu_temp: &Measurement, f: &mut Frame, area: Rect) {
     f.render_widget(b, area);
     f.render_widget(p, inner_area);
 }
+
+/// Build the line of the cpu power block, with the power of each RAPL domain
+/// kind summed across packages, gpu power, and the total of the measured
+/// parts.
+fn cpu_power_line(p: &SystemPollResult) -> Line<'static> {
+    let bold = |s: String| Span::styled(s, Style::new().add_modifier(Modifier::BOLD));
+    let watts = |w: Option<f32>| match w {
+        Some(w) => format!("{:.1}W  ", w),
+        None => "-  ".to_string(),
+    };
+
+    let mut spans = vec![];
+
+    for kind in RaplDomainKind::ALL {
+        if p.cpu_power.iter().any(|d| d.kind == kind) {
+            spans.push(bold(format!("{} ", kind.name())));
+            spans.push(Span::raw(watts(rapl::total_watts(&p.cpu_power, kind))));
+        }
+    }
+
+    let gpu_power: Vec<f32> = p.gpu_info.iter().filter_map(|g| g.power_usage).collect();
+
+    if !gpu_power.is_empty() {
+        spans.push(bold("GPU ".to_string()));
+        spans.push(Span::raw(watts(Some(gpu_power.iter().sum()))));
+    }
+
+    spans.push(bold("Total ".to_string()));
+    spans.push(Span::raw(watts(p.measured_power())));
+
+    Line::from(spans)
+}
+
+/// Draws a blocked element with the power consumption of the cpu packages,
+/// their core, uncore and DRAM domains, and the gpus, and their total.
+pub fn draw_cpu_power_block(p: &SystemPollResult, f: &mut Frame, area: Rect) {
+    let b = Block::default()
+        .borders(Borders::ALL)
+        .border_type(Rounded)
+        .title(" Power ");
+
+    f.render_widget(Paragraph::new(cpu_power_line(p)).block(b), area);
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cpu_time_bar() {
+        let times = CpuTimeBreakdown {
+            name: "cpu0".to_string(),
+            user: 50.0,
+            steal: 25.0,
+            idle: 25.0,
+            ..Default::default()
+        };
+
+        let line = cpu_time_bar(&times, 28);
+        let widths: Vec<usize> = line
+            .spans
+            .iter()
+            .map(|s| s.content.chars().count())
+            .collect();
+
+        // Name, bracket, user, 5 empty states, steal, guest, idle, bracket.
+        assert!(widths == [6, 1, 10, 0, 0, 0, 0, 0, 5, 0, 5, 1]);
+    }
+
+    #[test]
+    fn test_cgroup_usage_bar_colors() {
+        use crate::monitoring::cgroup::CgroupVersion;
+        use ratatui::{backend::TestBackend, Terminal};
+
+        let readings = vec![Measurement::default(); 8];
+        let cgroup = CgroupLimits {
+            version: CgroupVersion::V2,
+            path: "/".to_string(),
+            memory_limit: None,
+            memory_usage: None,
+            cpu_quota: Some(2.0),
+            cpuset: vec![],
+            cpu_usage: Some(0.5),
+        };
+
+        let mut terminal = Terminal::new(TestBackend::new(40, 12)).unwrap();
+        terminal
+            .draw(|f| draw_cpu_usage_block(&readings, &[], Some(&cgroup), f, f.size()))
+            .unwrap();
+
+        // The usage bar is the first line inside the border, and padding.
+        let buffer = terminal.backend().buffer();
+        let colors: Vec<Color> = (2..38)
+            .map(|x| buffer.get(x, 1))
+            .filter(|c| c.symbol() == "|" && c.fg != Color::DarkGray)
+            .map(|c| c.fg)
+            .collect();
+
+        // A quarter of a 2 cpu quota is green, not red.
+        assert!(!colors.is_empty() && colors.iter().all(|c| *c == Color::Green));
+    }
+
+    #[test]
+    fn test_summarize() {
+        assert!(summarize([Some("powersave"), Some("powersave")].into_iter()) == "powersave");
+        assert!(summarize([Some("powersave"), Some("performance")].into_iter()) == "mixed");
+        assert!(summarize([Some("powersave"), None].into_iter()) == "mixed");
+        assert!(summarize([None, None].into_iter()) == "n/a");
+    }
+
+    #[test]
+    fn test_cpu_power_line() {
+        use crate::monitoring::{
+            battery::{Battery, BatteryStatus},
+            rapl::RaplDomain,
+        };
+
+        let domain = |kind, watts| RaplDomain {
+            zone: String::new(),
+            kind,
+            package: 0,
+            watts,
+        };
+
+        let mut res = SystemPollResult {
+            cpu_power: vec![
+                domain(RaplDomainKind::Package, Some(30.0)),
+                domain(RaplDomainKind::Dram, Some(4.5)),
+            ],
+            ..Default::default()
+        };
+
+        let text = |res: &SystemPollResult| -> String {
+            cpu_power_line(res)
+                .spans
+                .iter()
+                .map(|s| s.content.as_ref())
+                .collect()
+        };
+
+        assert!(text(&res) == "Package 30.0W  DRAM 4.5W  Total 34.5W  ");
+
+        // The total covers the parts shown, even while a battery reports the
+        // power of the whole machine.
+        res.battery.batteries.push(Battery {
+            name: "BAT0".to_string(),
+            status: BatteryStatus::Discharging,
+            capacity: 50.0,
+            power: Some(12.5),
+            energy_now: None,
+            energy_full: None,
+            energy_full_design: None,
+        });
+
+        assert!(text(&res) == "Package 30.0W  DRAM 4.5W  Total 34.5W  ");
+
+        // Domains have no reading until they are polled twice.
+        res.cpu_power = vec![domain(RaplDomainKind::Package, None)];
+
+        assert!(text(&res) == "Package -  Total -  ");
+    }
+}
@@ -0,0 +1,151 @@
+// Contains functionality for drawing ui elements related to disk reporting.
+use human_bytes::human_bytes;
+use ratatui::{
+    layout::{Constraint, Layout, Rect},
+    style::{Style, Stylize},
+    text::Span,
+    widgets::{
+        block::{Position, Title},
+        Cell, Paragraph, Row, Table,
+    },
+    Frame,
+};
+
+use crate::monitoring::{diskio::DiskIoPollResult, system::DiskInformation};
+
+use super::{
+    state::DisksTabState,
+    util::{default_block, draw_disk_info, get_color_for_range, DISK_INFO_HEIGHT},
+};
+
+/// Height the disk I/O block needs to show every device.
+pub fn disk_io_block_height(disks: &[DiskIoPollResult]) -> u16 {
+    disks.len().max(1) as u16 + 3
+}
+
+/// Format an average request latency, or a dash if no requests completed.
+fn format_latency(latency_ms: Option<f64>) -> String {
+    match latency_ms {
+        Some(ms) if ms >= 100f64 => format!("{:.0}ms", ms),
+        Some(ms) => format!("{:.1}ms", ms),
+        None => "-".to_string(),
+    }
+}
+
+/// Draws a blocked table with the throughput, IOPS, latency, queue depth,
+/// and utilization of each block device.
+pub fn draw_disk_io_block(disks: &[DiskIoPollResult], f: &mut Frame, area: Rect) {
+    let block = default_block(" Disk I/O ");
+
+    if disks.is_empty() {
+        f.render_widget(
+            Paragraph::new("No block devices found.").dim().block(block),
+            area,
+        );
+        return;
+    }
+
+    let header = Row::new(vec![
+        "Device", "Read/s", "Write/s", "r IOPS", "w IOPS", "r lat", "w lat", "Queue", "Util %",
+    ])
+    .style(Style::new().bold());
+
+    let rows = disks.iter().map(|d| {
+        let utilization = d.utilization as f32;
+
+        Row::new(vec![
+            Cell::from(d.name.clone()),
+            Cell::from(human_bytes(d.read_bytes_per_sec)),
+            Cell::from(human_bytes(d.write_bytes_per_sec)),
+            Cell::from(format!("{:.0}", d.read_iops)),
+            Cell::from(format!("{:.0}", d.write_iops)),
+            Cell::from(format_latency(d.read_latency_ms)),
+            Cell::from(format_latency(d.write_latency_ms)),
+            Cell::from(format!("{:.2}", d.queue_depth)),
+            Cell::from(Span::styled(
+                format!("{:.1}", utilization),
+                Style::new().fg(get_color_for_range(utilization, (0f32, 100f32))),
+            )),
+        ])
+    });
+
+    let widths = vec![
+        Constraint::Min(8),
+        Constraint::Length(10),
+        Constraint::Length(10),
+        Constraint::Length(7),
+        Constraint::Length(7),
+        Constraint::Length(7),
+        Constraint::Length(7),
+        Constraint::Length(6),
+        Constraint::Length(6),
+    ];
+
+    f.render_widget(Table::new(rows, widths).header(header).block(block), area);
+}
+
+/// Draws the disks tab, which shows the usage of every mounted filesystem.
+///
+/// When there are more filesystems than fit, the list is scrolled to start
+/// at the offset in `state`.
+pub fn draw_disks_tab(
+    disks: &[DiskInformation],
+    state: &mut DisksTabState,
+    f: &mut Frame,
+    area: Rect,
+) {
+    let mut block = default_block(" Filesystems ");
+
+    let inner = block.inner(area);
+    let visible = (inner.height / DISK_INFO_HEIGHT).max(1) as usize;
+
+    state.offset = state.offset.min(disks.len().saturating_sub(visible));
+
+    if disks.len() > visible {
+        block = block.title(
+            Title::from(format!(
+                " ↑/↓: Scroll ({}-{} of {}) ",
+                state.offset + 1,
+                (state.offset + visible).min(disks.len()),
+                disks.len()
+            ))
+            .position(Position::Bottom),
+        );
+    }
+
+    f.render_widget(block, area);
+
+    if disks.is_empty() {
+        f.render_widget(Paragraph::new(" No filesystems found.").dim(), inner);
+        return;
+    }
+
+    let l = Layout::default()
+        .constraints(vec![Constraint::Length(DISK_INFO_HEIGHT); visible])
+        .split(inner);
+
+    for (disk, area) in disks.iter().skip(state.offset).zip(l.iter()) {
+        draw_disk_info(disk, f, *area);
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_latency() {
+        assert!(format_latency(Some(0.25)) == "0.2ms");
+        assert!(format_latency(Some(12.0)) == "12.0ms");
+        assert!(format_latency(Some(250.4)) == "250ms");
+        assert!(format_latency(None) == "-");
+    }
+
+    #[test]
+    fn test_disk_io_block_height() {
+        let disks = vec![DiskIoPollResult::default(); 2];
+
+        assert!(disk_io_block_height(&disks) == 5);
+        assert!(disk_io_block_height(&[]) == 4);
+    }
+}
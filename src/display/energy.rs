@@ -0,0 +1,114 @@
+// Contains functionality for drawing ui elements related to energy use.
+use ratatui::{
+    layout::Rect,
+    style::{Modifier, Style},
+    text::{Line, Span},
+    widgets::{
+        block::{Position, Title},
+        Block, BorderType, Borders, Padding, Paragraph,
+    },
+    Frame,
+};
+
+use crate::monitoring::energy::{EnergyRates, SessionEnergy};
+
+use super::util::format_uptime;
+
+/// Build the lines of the energy block, from the energy used during the
+/// session, and the current power.
+fn energy_lines(
+    energy: &SessionEnergy,
+    power: Option<f32>,
+    rates: &EnergyRates,
+) -> Vec<Line<'static>> {
+    let bold = |s: &str| Span::styled(s.to_string(), Style::new().add_modifier(Modifier::BOLD));
+
+    let power = match power {
+        Some(w) => format!("  ({:.1} W)", w),
+        None => String::new(),
+    };
+
+    let mut lines = vec![
+        Line::from(vec![
+            bold("Energy:  "),
+            Span::raw(format!("{:.3} Wh{}", energy.energy_wh, power)),
+        ]),
+        Line::from(vec![
+            bold("Session: "),
+            Span::raw(format_uptime(energy.elapsed_seconds as u64)),
+        ]),
+    ];
+
+    let mut estimates = vec![];
+
+    if let Some(cost) = rates.cost(energy.energy_wh) {
+        estimates.extend([bold("Cost:    "), Span::raw(format!("{:.3}  ", cost))]);
+    }
+
+    if let Some(grams) = rates.co2_grams(energy.energy_wh) {
+        estimates.extend([bold("CO2: "), Span::raw(format!("{:.1} g", grams))]);
+    }
+
+    if !estimates.is_empty() {
+        lines.push(Line::from(estimates));
+    }
+
+    lines
+}
+
+/// Height the energy block needs to show every line.
+pub fn energy_block_height(rates: &EnergyRates) -> u16 {
+    energy_lines(&SessionEnergy::default(), None, rates).len() as u16 + 2
+}
+
+/// Draws a blocked element with the energy used since the session started,
+/// or was last reset, and its estimated cost, and emissions.
+pub fn draw_energy_block(
+    energy: &SessionEnergy,
+    power: Option<f32>,
+    rates: &EnergyRates,
+    f: &mut Frame,
+    area: Rect,
+) {
+    let p = Paragraph::new(energy_lines(energy, power, rates)).block(
+        Block::new()
+            .title(" Energy ")
+            .title(Title::from(" e: Reset ").position(Position::Bottom))
+            .borders(Borders::ALL)
+            .border_type(BorderType::Rounded)
+            .padding(Padding::new(2, 2, 0, 0)),
+    );
+
+    f.render_widget(p, area)
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+
+    #[test]
+    fn test_energy_lines() {
+        let energy = SessionEnergy {
+            energy_wh: 500.0,
+            elapsed_seconds: 5400.0,
+        };
+
+        let rates = EnergyRates {
+            price_per_kwh: Some(0.3),
+            co2_grams_per_kwh: Some(400.0),
+        };
+
+        let text: Vec<String> = energy_lines(&energy, Some(42.0), &rates)
+            .iter()
+            .map(|l| l.spans.iter().map(|s| s.content.as_ref()).collect())
+            .collect();
+
+        assert!(text[0] == "Energy:  500.000 Wh  (42.0 W)");
+        assert!(text[1] == "Session: 1h 30m");
+        assert!(text[2] == "Cost:    0.150  CO2: 200.0 g");
+
+        // Estimates are only shown when rates are configured.
+        assert!(energy_block_height(&rates) == 5);
+        assert!(energy_block_height(&EnergyRates::default()) == 4);
+    }
+}
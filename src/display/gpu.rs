@@ -1,18 +1,41 @@
 // Contains functionality for drawing ui elements related to gpu reporting.
+use human_bytes::human_bytes;
 use ratatui::{
-    layout::{Constraint, Layout, Rect},
-    style::{Style, Stylize},
-    widgets::{Block, Borders, Padding, Paragraph},
+    layout::{Alignment, Constraint, Direction, Layout, Rect},
+    style::{Color, Modifier, Style, Stylize},
+    text::{Line, Span},
+    widgets::{
+        block::{Position, Title},
+        Block, BorderType, Borders, Cell, Padding, Paragraph, Row, Sparkline, Table, TableState,
+        Wrap,
+    },
     Frame,
 };
 
-use crate::monitoring::polling::GpuPollResult;
+use crate::monitoring::{
+    gpu::{GpuError, GpuHealth},
+    history::PollHistory,
+    polling::{GpuPollResult, GpuProcess},
+    process::ProcessPollResult,
+};
 
-use super::{bar_chart::generate_bar_chart, util::default_block};
+use super::{
+    bar_chart::generate_bar_chart,
+    state::GpuTabState,
+    util::{default_block, get_color_for_range},
+};
 
-/// Draws a blocked element reporting gpu name, average usage, and temperature.
-pub fn draw_gpu_info_block(gpu_data: &Vec<GpuPollResult>, f: &mut Frame, area: Rect) {
-    let gpu_block_height = 8;
+/// Draws a blocked element reporting gpu name, average usage, temperature,
+/// and any additional metrics the device supports.
+///
+/// Any gpu backend errors are listed below the gpu blocks.
+pub fn draw_gpu_info_block(
+    gpu_data: &[GpuPollResult],
+    errors: &[GpuError],
+    f: &mut Frame,
+    area: Rect,
+) {
+    let gpu_block_height = 15;
 
     let mut gpu_constraints = vec![Constraint::Length(gpu_block_height); gpu_data.len()];
     gpu_constraints.push(Constraint::Percentage(99));
@@ -28,6 +51,7 @@ pub fn draw_gpu_info_block(gpu_data: &Vec<GpuPollResult>, f: &mut Frame, area: R
             .borders(Borders::ALL)
             .border_style(Style::new().dark_gray())
             .title(format!(" {} ", gpu.name))
+            .title(health_badges(&gpu.health))
             .title_style(Style::new().white().bold())
             .padding(Padding {
                 left: 1,
@@ -46,6 +70,7 @@ pub fn draw_gpu_info_block(gpu_data: &Vec<GpuPollResult>, f: &mut Frame, area: R
                 Constraint::Length(3),
                 Constraint::Length(3),
                 Constraint::Length(3),
+                Constraint::Length(4),
             ])
             .split(current_gpu_layout);
 
@@ -57,7 +82,7 @@ pub fn draw_gpu_info_block(gpu_data: &Vec<GpuPollResult>, f: &mut Frame, area: R
                 gpu.temp,
                 (0f32, 100f32),
                 8,
-                current_gpu_layout.width as usize - 2,
+                current_gpu_layout.width.saturating_sub(2) as usize,
             ))
             .block(default_block(" GPU Temp (C) ")),
             l[0],
@@ -71,10 +96,708 @@ pub fn draw_gpu_info_block(gpu_data: &Vec<GpuPollResult>, f: &mut Frame, area: R
                 gpu.usage,
                 (0f32, 100f32),
                 8,
-                current_gpu_layout.width as usize - 2,
+                current_gpu_layout.width.saturating_sub(2) as usize,
             ))
             .block(default_block(" GPU Usage (avg) ")),
             l[1],
-        )
+        );
+
+        // Gpu memory
+        let (memory_percent, memory_str) = match gpu.memory_total {
+            0 => (0f32, " n/a".to_string()),
+            total => {
+                let percent = 100f32 * gpu.memory_used as f32 / total as f32;
+                (percent, format!(" {:.0}%", percent))
+            }
+        };
+        let memory_title = format!(
+            " VRAM ({} / {}) ",
+            human_bytes(gpu.memory_used as f64),
+            human_bytes(gpu.memory_total as f64)
+        );
+        f.render_widget(
+            Paragraph::new(*generate_bar_chart(
+                &memory_str,
+                memory_percent,
+                (0f32, 100f32),
+                8,
+                current_gpu_layout.width.saturating_sub(2) as usize,
+            ))
+            .block(default_block(&memory_title)),
+            l[2],
+        );
+
+        f.render_widget(Paragraph::new(gpu_details(gpu)), l[3]);
+    }
+
+    draw_gpu_errors(errors, f, gpu_layout[gpu_data.len()]);
+}
+
+/// Draws the gpu tab, which contains one full width panel per gpu, showing
+/// the history of each of its metrics, and the processes using it.
+///
+/// The rows of all process tables are selectable as one list. The selected
+/// row is written back to `state`, along with its pid if the process can be
+/// shown in the process table.
+pub fn draw_gpu_tab(poll_data: &PollHistory, state: &mut GpuTabState, f: &mut Frame, area: Rect) {
+    let latest = poll_data.latest();
+
+    if latest.gpu_info.is_empty() {
+        let l = Layout::default()
+            .constraints(vec![Constraint::Length(2), Constraint::Min(0)])
+            .split(area);
+
+        f.render_widget(Paragraph::new(" No gpus found.").dim(), l[0]);
+        draw_gpu_errors(&latest.gpu_errors, f, l[1]);
+
+        return;
+    }
+
+    let device_count = latest.gpu_info.len() as u32;
+
+    let mut constraints = vec![Constraint::Ratio(1, device_count); latest.gpu_info.len()];
+    constraints.push(Constraint::Length(latest.gpu_errors.len() as u16));
+
+    let l = Layout::default().constraints(constraints).split(area);
+
+    let process_count = latest
+        .gpu_info
+        .iter()
+        .map(|g| g.processes.len())
+        .sum::<usize>();
+    state.selected = state.selected.min(process_count.saturating_sub(1));
+    state.selected_pid = None;
+
+    let mut first_row = 0;
+
+    for (i, gpu) in latest.gpu_info.iter().enumerate() {
+        // Readings of this device, from oldest to newest. Polls in which the
+        // device was missing are kept as gaps.
+        let history: Vec<Option<&GpuPollResult>> = poll_data.gpu_info().map(|g| g.get(i)).collect();
+
+        let selected = state
+            .selected
+            .checked_sub(first_row)
+            .filter(|s| *s < gpu.processes.len());
+
+        if let Some(s) = selected {
+            let pid = gpu.processes[s].pid;
+
+            if latest.processes.iter().any(|p| p.pid == pid) {
+                state.selected_pid = Some(pid);
+            }
+        }
+
+        draw_gpu_panel(
+            gpu,
+            &history,
+            &latest.processes,
+            selected,
+            state.show_health,
+            f,
+            l[i],
+        );
+
+        first_row += gpu.processes.len();
+    }
+
+    draw_gpu_errors(&latest.gpu_errors, f, l[latest.gpu_info.len()]);
+}
+
+/// A gpu metric, drawn as a history graph on the gpu tab.
+struct GpuMetric {
+    title: &'static str,
+    value: fn(&GpuPollResult) -> Option<f64>,
+    format: fn(f64) -> String,
+    /// Upper bound of the graph. When `None`, the graph is scaled to the
+    /// largest value in the history.
+    max: fn(&GpuPollResult) -> Option<f64>,
+}
+
+static GPU_METRICS: [GpuMetric; 11] = [
+    GpuMetric {
+        title: "Usage",
+        value: |g| Some(g.usage as f64),
+        format: |v| format!("{:.0}%", v),
+        max: |_| Some(100f64),
+    },
+    GpuMetric {
+        title: "Temp",
+        value: |g| Some(g.temp as f64),
+        format: |v| format!("{:.0}C", v),
+        max: |_| Some(100f64),
+    },
+    GpuMetric {
+        title: "VRAM",
+        value: |g| (g.memory_total > 0).then_some(g.memory_used as f64),
+        format: human_bytes,
+        max: |g| (g.memory_total > 0).then_some(g.memory_total as f64),
+    },
+    GpuMetric {
+        title: "Power",
+        value: |g| g.power_usage.map(|p| p as f64),
+        format: |v| format!("{:.1} W", v),
+        max: |g| g.power_limit.map(|p| p as f64),
+    },
+    GpuMetric {
+        title: "Fan",
+        value: |g| g.fan_speed.map(|s| s as f64),
+        format: |v| format!("{:.0}%", v),
+        max: |_| Some(100f64),
+    },
+    GpuMetric {
+        title: "Core clock",
+        value: |g| g.graphics_clock.map(|c| c as f64),
+        format: |v| format!("{:.0} MHz", v),
+        max: |_| None,
+    },
+    GpuMetric {
+        title: "Mem clock",
+        value: |g| g.memory_clock.map(|c| c as f64),
+        format: |v| format!("{:.0} MHz", v),
+        max: |_| None,
+    },
+    GpuMetric {
+        title: "PCIe TX",
+        value: |g| g.pcie_tx.map(|t| t as f64),
+        format: |v| format!("{}/s", human_bytes(v)),
+        max: |_| None,
+    },
+    GpuMetric {
+        title: "PCIe RX",
+        value: |g| g.pcie_rx.map(|t| t as f64),
+        format: |v| format!("{}/s", human_bytes(v)),
+        max: |_| None,
+    },
+    GpuMetric {
+        title: "Encoder",
+        value: |g| g.encoder_usage.map(|u| u as f64),
+        format: |v| format!("{:.0}%", v),
+        max: |_| Some(100f64),
+    },
+    GpuMetric {
+        title: "Decoder",
+        value: |g| g.decoder_usage.map(|u| u as f64),
+        format: |v| format!("{:.0}%", v),
+        max: |_| Some(100f64),
+    },
+];
+
+/// Minimum width of a single metric graph on the gpu tab.
+const GPU_METRIC_WIDTH: u16 = 28;
+
+/// Draws the panel of a single gpu, with a grid of history graphs, one per
+/// metric, above a table of the processes using the gpu.
+fn draw_gpu_panel(
+    gpu: &GpuPollResult,
+    history: &[Option<&GpuPollResult>],
+    processes: &[ProcessPollResult],
+    selected: Option<usize>,
+    show_health: bool,
+    f: &mut Frame,
+    area: Rect,
+) {
+    let hint = match show_health {
+        true => " h: Hide health ",
+        false => " h: Show health ",
+    };
+
+    let b = Block::new()
+        .border_type(BorderType::Rounded)
+        .borders(Borders::ALL)
+        .border_style(Style::new().dark_gray())
+        .title(format!(" {} ", gpu.name))
+        .title(health_badges(&gpu.health))
+        .title(Title::from(hint).position(Position::Bottom))
+        .title_style(Style::new().white().bold());
+
+    let mut inner = b.inner(area);
+
+    f.render_widget(b, area);
+
+    if !gpu.processes.is_empty() {
+        // The header, and borders take up three rows.
+        let table_height = (gpu.processes.len() as u16 + 3).min(inner.height / 2);
+
+        let l = Layout::default()
+            .constraints(vec![Constraint::Min(0), Constraint::Length(table_height)])
+            .split(inner);
+
+        inner = l[0];
+
+        draw_gpu_process_table(&gpu.processes, processes, selected, f, l[1]);
+    }
+
+    if show_health {
+        let l = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints(vec![Constraint::Min(0), Constraint::Length(48)])
+            .split(inner);
+
+        inner = l[0];
+
+        f.render_widget(
+            Paragraph::new(health_details(&gpu.health))
+                .wrap(Wrap { trim: false })
+                .block(default_block(" Health ").border_style(Style::new().dark_gray())),
+            l[1],
+        );
+    }
+
+    let columns = (inner.width / GPU_METRIC_WIDTH).clamp(1, GPU_METRICS.len() as u16) as usize;
+    let rows = GPU_METRICS.len().div_ceil(columns);
+
+    let row_layout = Layout::default()
+        .constraints(vec![Constraint::Ratio(1, rows as u32); rows])
+        .split(inner);
+
+    for (r, row_area) in row_layout.iter().enumerate() {
+        let cells = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints(vec![Constraint::Ratio(1, columns as u32); columns])
+            .split(*row_area);
+
+        for (metric, cell) in GPU_METRICS.iter().skip(r * columns).zip(cells.iter()) {
+            draw_gpu_metric(metric, gpu, history, f, *cell);
+        }
+    }
+}
+
+/// Draws the history graph of a single gpu metric, titled with its current
+/// value.
+fn draw_gpu_metric(
+    metric: &GpuMetric,
+    gpu: &GpuPollResult,
+    history: &[Option<&GpuPollResult>],
+    f: &mut Frame,
+    area: Rect,
+) {
+    let title = format!(
+        " {}: {} ",
+        metric.title,
+        or_na((metric.value)(gpu), metric.format)
+    );
+
+    let b = default_block(&title).border_style(Style::new().dark_gray());
+    let inner = b.inner(area);
+
+    // Sparklines draw their data from the left, so only the most recent
+    // readings which fit are kept.
+    let values: Vec<f64> = history
+        .iter()
+        .skip(history.len().saturating_sub(inner.width as usize))
+        .map(|g| g.and_then(metric.value).unwrap_or(0f64))
+        .collect();
+
+    let max = (metric.max)(gpu)
+        .unwrap_or_else(|| values.iter().copied().fold(0f64, f64::max))
+        .max(1f64);
+
+    let data: Vec<u64> = values.iter().map(|v| v.round() as u64).collect();
+
+    f.render_widget(
+        Sparkline::default()
+            .block(b)
+            .data(&data)
+            .max(max.round() as u64)
+            .style(Style::new().green()),
+        area,
+    );
+}
+
+/// Draws the table of processes using a gpu.
+///
+/// Processes which are also listed in the process table are linked to it,
+/// and show their user and cpu usage. Others, such as processes in another
+/// pid namespace, are dimmed.
+fn draw_gpu_process_table(
+    gpu_processes: &[GpuProcess],
+    processes: &[ProcessPollResult],
+    selected: Option<usize>,
+    f: &mut Frame,
+    area: Rect,
+) {
+    let header =
+        Row::new(vec!["PID", "Name", "User", "CPU %", "VRAM", "SM %"]).style(Style::new().bold());
+
+    let rows = gpu_processes.iter().map(|g| {
+        let process = processes.iter().find(|p| p.pid == g.pid);
+
+        let cells = vec![
+            Cell::from(g.pid.to_string()),
+            Cell::from(g.name.clone()),
+            Cell::from(process.map_or("-".to_string(), |p| p.user.clone())),
+            Cell::from(process.map_or(Span::raw("-"), |p| {
+                Span::styled(
+                    format!("{:.1}", p.cpu_usage),
+                    Style::new().fg(get_color_for_range(p.cpu_usage, (0f32, 100f32))),
+                )
+            })),
+            Cell::from(or_na(g.memory_used, |m| human_bytes(m as f64))),
+            Cell::from(or_na(g.sm_usage, |u| u.to_string())),
+        ];
+
+        match process {
+            Some(_) => Row::new(cells),
+            None => Row::new(cells).dim(),
+        }
+    });
+
+    let widths = vec![
+        Constraint::Length(8),
+        Constraint::Percentage(30),
+        Constraint::Length(12),
+        Constraint::Length(7),
+        Constraint::Length(10),
+        Constraint::Length(6),
+    ];
+
+    let mut block = default_block(" Processes ").border_style(Style::new().dark_gray());
+
+    if selected.is_some() {
+        block = block.title(
+            Title::from(" ↑/↓: Select  Enter: Show in process table ").position(Position::Bottom),
+        );
+    }
+
+    let table = Table::new(rows, widths)
+        .header(header)
+        .block(block)
+        .highlight_style(Style::new().bg(Color::DarkGray).bold());
+
+    f.render_stateful_widget(
+        table,
+        area,
+        &mut TableState::default().with_selected(selected),
+    );
+}
+
+/// Build a right aligned block title, with a badge for each health warning
+/// of a gpu.
+fn health_badges(health: &GpuHealth) -> Title<'static> {
+    let badges: Vec<Span> = health
+        .warnings()
+        .into_iter()
+        .flat_map(|w| {
+            [
+                Span::styled(format!(" {} ", w), Style::new().black().on_red().bold()),
+                Span::raw(" "),
+            ]
+        })
+        .collect();
+
+    Title::from(Line::from(badges)).alignment(Alignment::Right)
+}
+
+/// Build the lines describing the health of a gpu in detail.
+fn health_details(health: &GpuHealth) -> Vec<Line<'static>> {
+    let style_category =
+        |s: &'static str| Span::styled(s, Style::new().add_modifier(Modifier::BOLD));
+
+    let mut lines = vec![
+        Line::from(vec![
+            style_category("Performance state: "),
+            Span::raw(or_na(health.performance_state, |p| format!("P{}", p))),
+        ]),
+        Line::from(vec![
+            style_category("ECC errors:        "),
+            Span::raw(match (health.ecc_corrected, health.ecc_uncorrected) {
+                (None, None) => "n/a".to_string(),
+                (corrected, uncorrected) => format!(
+                    "{} corrected, {} uncorrected",
+                    or_na(corrected, |e| e.to_string()),
+                    or_na(uncorrected, |e| e.to_string())
+                ),
+            }),
+        ]),
+        Line::from(vec![
+            style_category("Retired pages:     "),
+            Span::raw(or_na(health.retired_pages, |p| p.to_string())),
+            Span::raw(match health.retired_pages_pending {
+                Some(true) => " (more pending reboot)",
+                _ => "",
+            }),
+        ]),
+        Line::from(style_category("Throttle reasons:")),
+    ];
+
+    match health.throttle_reasons.is_empty() {
+        true => lines.push(Line::styled("  none", Style::new().dim())),
+        false => lines.extend(
+            health
+                .throttle_reasons
+                .iter()
+                .map(|r| Line::from(format!("  {}", r.description()))),
+        ),
+    }
+
+    lines
+}
+
+/// Draws the errors reported by gpu backends, one per line.
+fn draw_gpu_errors(errors: &[GpuError], f: &mut Frame, area: Rect) {
+    if errors.is_empty() {
+        return;
+    }
+
+    let lines: Vec<Line> = errors
+        .iter()
+        .map(|e| Line::styled(format!(" {}", e), Style::new().red()))
+        .collect();
+
+    f.render_widget(Paragraph::new(lines).wrap(Wrap { trim: false }), area);
+}
+
+/// Format an optional metric, or "n/a" if the device does not report it.
+fn or_na<T>(value: Option<T>, format: impl Fn(T) -> String) -> String {
+    value.map_or_else(|| "n/a".to_string(), format)
+}
+
+/// Build the detail lines of a gpu block: power, fan, clocks, pcie
+/// throughput, and video engine usage.
+fn gpu_details(gpu: &GpuPollResult) -> Vec<Line<'static>> {
+    let style_category =
+        |s: &'static str| Span::styled(s, Style::new().add_modifier(Modifier::BOLD));
+
+    let power = match (gpu.power_usage, gpu.power_limit) {
+        (Some(usage), Some(limit)) => format!("{:.1} / {:.0} W", usage, limit),
+        (usage, _) => or_na(usage, |u| format!("{:.1} W", u)),
+    };
+
+    vec![
+        Line::from(vec![
+            style_category("Power:  "),
+            Span::raw(format!("{:<18}", power)),
+            style_category("Fan: "),
+            Span::raw(or_na(gpu.fan_speed, |s| format!("{}%", s))),
+        ]),
+        Line::from(vec![
+            style_category("Clocks: "),
+            Span::raw(format!(
+                "core {}  mem {}",
+                or_na(gpu.graphics_clock, |c| format!("{} MHz", c)),
+                or_na(gpu.memory_clock, |c| format!("{} MHz", c)),
+            )),
+        ]),
+        Line::from(vec![
+            style_category("PCIe:   "),
+            Span::raw(format!(
+                "tx {}  rx {}",
+                or_na(gpu.pcie_tx, |t| format!("{}/s", human_bytes(t as f64))),
+                or_na(gpu.pcie_rx, |t| format!("{}/s", human_bytes(t as f64))),
+            )),
+        ]),
+        Line::from(vec![
+            style_category("Video:  "),
+            Span::raw(format!(
+                "enc {}  dec {}",
+                or_na(gpu.encoder_usage, |u| format!("{}%", u)),
+                or_na(gpu.decoder_usage, |u| format!("{}%", u)),
+            )),
+        ]),
+    ]
+}
+
+#[cfg(test)]
+pub mod tests {
+    use ratatui::{backend::TestBackend, Terminal};
+
+    use super::*;
+    use crate::monitoring::gpu::{mock::MockGpuBackend, poll_backends, GpuBackend, ThrottleReason};
+    use crate::monitoring::polling::SystemPollResult;
+
+    /// Render the gpu block for the given backends, and return the rendered
+    /// text, one string per row.
+    fn render(mut backends: Vec<Box<dyn GpuBackend>>) -> Vec<String> {
+        let (gpus, errors) = poll_backends(&mut backends);
+
+        render_with(60, 20, |f| draw_gpu_info_block(&gpus, &errors, f, f.size()))
+    }
+
+    /// Render using the given draw function, and return the rendered text.
+    fn render_with(width: u16, height: u16, draw: impl FnOnce(&mut Frame)) -> Vec<String> {
+        let mut terminal = Terminal::new(TestBackend::new(width, height)).unwrap();
+        terminal.draw(draw).unwrap();
+
+        let buffer = terminal.backend().buffer();
+
+        (0..buffer.area.height)
+            .map(|y| {
+                (0..buffer.area.width)
+                    .map(|x| buffer.get(x, y).symbol())
+                    .collect()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_draw_mock_gpu() {
+        let rows = render(vec![Box::new(MockGpuBackend::new(vec![GpuPollResult {
+            name: "Mock GPU 9000".to_string(),
+            temp: 61.0,
+            usage: 37.0,
+            ..Default::default()
+        }]))]);
+
+        assert!(rows[0].contains(" Mock GPU 9000 "));
+        assert!(rows.iter().any(|r| r.contains("61C")));
+        assert!(rows.iter().any(|r| r.contains("37%")));
+    }
+
+    #[test]
+    fn test_draw_extended_metrics() {
+        let rows = render(vec![Box::new(MockGpuBackend::new(vec![GpuPollResult {
+            power_usage: Some(212.5),
+            power_limit: Some(350.0),
+            fan_speed: Some(55),
+            graphics_clock: Some(1890),
+            pcie_tx: Some(2048),
+            encoder_usage: Some(12),
+            ..Default::default()
+        }]))]);
+
+        assert!(rows.iter().any(|r| r.contains("212.5 / 350 W")));
+        assert!(rows.iter().any(|r| r.contains("Fan: 55%")));
+        assert!(rows.iter().any(|r| r.contains("core 1890 MHz  mem n/a")));
+        assert!(rows.iter().any(|r| r.contains("tx 2 KiB/s  rx n/a")));
+        assert!(rows.iter().any(|r| r.contains("enc 12%  dec n/a")));
+    }
+
+    #[test]
+    fn test_draw_vram() {
+        let rows = render(vec![Box::new(MockGpuBackend::new(vec![GpuPollResult {
+            memory_used: 4 * 1024 * 1024 * 1024,
+            memory_total: 16 * 1024 * 1024 * 1024,
+            ..Default::default()
+        }]))]);
+
+        assert!(rows.iter().any(|r| r.contains(" VRAM (4 GiB / 16 GiB) ")));
+        assert!(rows.iter().any(|r| r.contains(" 25%")));
+    }
+
+    #[test]
+    fn test_draw_gpu_tab() {
+        let mut history = PollHistory::new(10);
+
+        for usage in [10f32, 20f32, 30f32] {
+            history.add(SystemPollResult {
+                gpu_info: vec![
+                    GpuPollResult {
+                        name: "Mock GPU 0".to_string(),
+                        usage,
+                        ..Default::default()
+                    },
+                    GpuPollResult {
+                        name: "Mock GPU 1".to_string(),
+                        power_usage: Some(100f32),
+                        ..Default::default()
+                    },
+                ],
+                ..Default::default()
+            });
+        }
+
+        let rows = render_with(120, 40, |f| {
+            draw_gpu_tab(&history, &mut GpuTabState::new(), f, f.size())
+        });
+
+        assert!(rows[0].contains(" Mock GPU 0 "));
+        assert!(rows.iter().any(|r| r.contains(" Mock GPU 1 ")));
+        assert!(rows.iter().any(|r| r.contains(" Usage: 30% ")));
+        assert!(rows.iter().any(|r| r.contains(" Power: 100.0 W ")));
+        assert!(rows.iter().any(|r| r.contains(" Decoder: n/a ")));
+    }
+
+    #[test]
+    fn test_draw_gpu_processes() {
+        let mut history = PollHistory::new(10);
+
+        history.add(SystemPollResult {
+            gpu_info: vec![GpuPollResult {
+                processes: vec![
+                    GpuProcess {
+                        pid: 42,
+                        name: "trainer".to_string(),
+                        memory_used: Some(3 * 1024 * 1024 * 1024),
+                        sm_usage: Some(97),
+                    },
+                    GpuProcess {
+                        pid: 43,
+                        name: "containerized".to_string(),
+                        ..Default::default()
+                    },
+                ],
+                ..Default::default()
+            }],
+            processes: vec![ProcessPollResult {
+                pid: 42,
+                user: "alice".to_string(),
+                ..Default::default()
+            }],
+            ..Default::default()
+        });
+
+        let mut state = GpuTabState::new();
+        let rows = render_with(120, 40, |f| draw_gpu_tab(&history, &mut state, f, f.size()));
+
+        let trainer = rows.iter().find(|r| r.contains("trainer")).unwrap();
+        assert!(trainer.contains("alice"));
+        assert!(trainer.contains("3 GiB"));
+        assert!(trainer.contains("97"));
+        assert!(state.selected_pid == Some(42));
+
+        // Processes missing from the process table can not be linked to.
+        state.selected = 1;
+        render_with(120, 40, |f| draw_gpu_tab(&history, &mut state, f, f.size()));
+        assert!(state.selected_pid.is_none());
+    }
+
+    #[test]
+    fn test_draw_gpu_health() {
+        let mut history = PollHistory::new(10);
+
+        history.add(SystemPollResult {
+            gpu_info: vec![GpuPollResult {
+                health: GpuHealth {
+                    performance_state: Some(2),
+                    throttle_reasons: vec![ThrottleReason::HwThermal],
+                    ..Default::default()
+                },
+                ..Default::default()
+            }],
+            ..Default::default()
+        });
+
+        let mut state = GpuTabState::new();
+        let rows = render_with(120, 30, |f| draw_gpu_tab(&history, &mut state, f, f.size()));
+
+        assert!(rows[0].contains(" THERMAL "));
+        assert!(!rows.iter().any(|r| r.contains("Performance state")));
+
+        state.show_health = true;
+        let rows = render_with(120, 30, |f| draw_gpu_tab(&history, &mut state, f, f.size()));
+
+        assert!(rows.iter().any(|r| r.contains("Performance state: P2")));
+        assert!(rows.iter().any(|r| r.contains("ECC errors:        n/a")));
+        assert!(rows.iter().any(|r| r.contains("Hardware thermal slowdown")));
+    }
+
+    #[test]
+    fn test_draw_gpu_tab_without_gpus() {
+        let mut history = PollHistory::new(10);
+        history.add(SystemPollResult::default());
+
+        let rows = render_with(60, 10, |f| {
+            draw_gpu_tab(&history, &mut GpuTabState::new(), f, f.size())
+        });
+
+        assert!(rows[0].contains("No gpus found."));
+    }
+
+    #[test]
+    fn test_draw_backend_error() {
+        let rows = render(vec![Box::new(MockGpuBackend::failing("driver not loaded"))]);
+
+        assert!(rows[0].contains("mock: driver not loaded"));
     }
 }
@@ -2,46 +2,145 @@ use human_bytes::human_bytes;
 use ratatui::style::Modifier;
 use ratatui::{
     layout::Rect,
-    style::Style,
+    style::{Color, Style},
     text::{Line, Span},
     widgets::{Block, BorderType, Borders, Padding, Paragraph},
     Frame,
 };
 
-use super::{bar_chart::generate_bar_chart, util::get_color_for_range};
-
-/// Draws a blocked element with information about total, and used memory.
-pub fn draw_memory_usage_block(total_memory: f32, used_memory: f32, f: &mut Frame, area: Rect) {
-    let usage_percent_text = format!("{}%", (100f32 * used_memory / total_memory) as usize);
-
-    let text = vec![
-        Line::from(vec![
-            Span::styled(
-                "Total Memory: ".to_string(),
-                Style::new().add_modifier(Modifier::BOLD),
-            ),
-            Span::styled(human_bytes(total_memory), Style::new()),
-        ]),
-        Line::from(vec![
-            Span::styled(
-                "Used Memory:  ".to_string(),
-                Style::new().add_modifier(Modifier::BOLD),
-            ),
-            Span::styled(
-                human_bytes(used_memory),
-                Style::new().fg(get_color_for_range(used_memory, (0f32, total_memory))),
-            ),
-        ]),
-        *generate_bar_chart(
-            &usage_percent_text,
-            used_memory,
-            (0f32, total_memory),
+use crate::monitoring::{cgroup::CgroupLimits, memory::MemoryPollResult};
+
+use super::bar_chart::{generate_bar_chart, generate_stacked_bar};
+
+/// Legend label, size, and color of a part of memory.
+type MemorySegment = (&'static str, fn(&MemoryPollResult) -> u64, Color);
+
+/// Parts of memory shown in the stacked bar, from left to right.
+const MEMORY_SEGMENTS: [MemorySegment; 4] = [
+    ("Used", MemoryPollResult::used, Color::Green),
+    ("Buffers", |m| m.buffers, Color::Blue),
+    ("Cache", MemoryPollResult::cache, Color::Yellow),
+    ("Free", |m| m.free, Color::DarkGray),
+];
+
+/// Height the memory block needs to show every line.
+pub fn memory_block_height(memory: &MemoryPollResult, cgroup: Option<&CgroupLimits>) -> u16 {
+    memory_lines(memory, cgroup, 0).len() as u16 + 2
+}
+
+/// Build the lines of the memory block, with bars `width` characters wide.
+///
+/// If the cgroup mainframe runs in has a memory limit, its usage against
+/// that limit is shown below the total.
+fn memory_lines(
+    m: &MemoryPollResult,
+    cgroup: Option<&CgroupLimits>,
+    width: usize,
+) -> Vec<Line<'static>> {
+    let bold = |s: &str| Span::styled(s.to_string(), Style::new().add_modifier(Modifier::BOLD));
+    let value = |label: &str, bytes: u64| format!("{} {}  ", label, human_bytes(bytes as f64));
+
+    let segments: Vec<(u64, Color)> = MEMORY_SEGMENTS
+        .iter()
+        .take(3)
+        .map(|(_, size, color)| (size(m), *color))
+        .collect();
+
+    let legend: Vec<Span> = MEMORY_SEGMENTS
+        .iter()
+        .flat_map(|(label, size, color)| {
+            [
+                Span::styled("■ ", Style::new().fg(*color)),
+                Span::raw(value(label, size(m))),
+            ]
+        })
+        .collect();
+
+    let mut lines = vec![Line::from(vec![
+        bold("Total: "),
+        Span::raw(format!("{}  ", human_bytes(m.total as f64))),
+        bold("Available: "),
+        Span::raw(human_bytes(m.available as f64)),
+    ])];
+
+    if let Some((c, limit)) = cgroup.and_then(|c| Some((c, c.memory_limit?))) {
+        let usage = c.memory_usage.unwrap_or(0);
+
+        lines.push(Line::from(vec![
+            bold("Limit: "),
+            Span::raw(format!(
+                "{} / {}  ",
+                human_bytes(usage as f64),
+                human_bytes(limit as f64)
+            )),
+            Span::styled(c.version.name(), Style::new().fg(Color::Yellow)),
+        ]));
+        lines.push(*generate_bar_chart(
+            "Used",
+            usage.min(limit) as f32,
+            (0f32, limit.max(1) as f32),
             5,
-            area.width as usize - 6,
-        ),
-    ];
+            width,
+        ));
+    }
+
+    lines.extend([
+        generate_stacked_bar(&segments, m.total, width),
+        Line::from(legend[..4].to_vec()),
+        Line::from(legend[4..].to_vec()),
+        Line::from(value("Shared", m.shared) + &value("Slab", m.slab)),
+        Line::from(value("Dirty", m.dirty) + &value("Writeback", m.writeback)),
+    ]);
+
+    if m.hugepages_total > 0 {
+        lines.push(Line::from(format!(
+            "Hugepages {}/{} free ({})",
+            m.hugepages_free,
+            m.hugepages_total,
+            human_bytes(m.hugepage_size as f64)
+        )));
+    }
 
-    let p = Paragraph::new(text).block(
+    if m.swap_total == 0 {
+        lines.push(Line::from(vec![bold("Swap: "), Span::raw("none")]));
+
+        return lines;
+    }
+
+    lines.push(*generate_bar_chart(
+        "Swap",
+        m.swap_used() as f32,
+        (0f32, m.swap_total as f32),
+        5,
+        width,
+    ));
+    lines.push(Line::from(vec![
+        Span::raw(value("Swap used", m.swap_used())),
+        Span::raw(format!(
+            "in {}/s  out {}/s",
+            human_bytes(m.swap_in_per_sec),
+            human_bytes(m.swap_out_per_sec)
+        )),
+    ]));
+
+    lines
+}
+
+/// Draws a blocked element with a breakdown of memory usage into used
+/// memory, buffers, cache, and free memory, followed by swap usage, and the
+/// memory limit of the cgroup mainframe runs in, if there is one.
+pub fn draw_memory_usage_block(
+    memory: &MemoryPollResult,
+    cgroup: Option<&CgroupLimits>,
+    f: &mut Frame,
+    area: Rect,
+) {
+    let p = Paragraph::new(memory_lines(
+        memory,
+        cgroup,
+        area.width.saturating_sub(6) as usize,
+    ))
+    .block(
         Block::new()
             .title(" Memory ")
             .borders(Borders::ALL)
@@ -51,3 +150,77 @@ pub fn draw_memory_usage_block(total_memory: f32, used_memory: f32, f: &mut Fram
 
     f.render_widget(p, area)
 }
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+    use crate::monitoring::cgroup::CgroupVersion;
+
+    #[test]
+    fn test_memory_block_height() {
+        let mut memory = MemoryPollResult {
+            total: 1000,
+            ..Default::default()
+        };
+
+        assert!(memory_block_height(&memory, None) == 9);
+
+        memory.swap_total = 500;
+        memory.hugepages_total = 4;
+
+        assert!(memory_block_height(&memory, None) == 11);
+
+        let mut cgroup = CgroupLimits {
+            version: CgroupVersion::V2,
+            path: "/".to_string(),
+            memory_limit: None,
+            memory_usage: Some(100),
+            cpu_quota: None,
+            cpuset: vec![],
+            cpu_usage: None,
+        };
+
+        assert!(memory_block_height(&memory, Some(&cgroup)) == 11);
+
+        cgroup.memory_limit = Some(500);
+
+        assert!(memory_block_height(&memory, Some(&cgroup)) == 13);
+    }
+
+    #[test]
+    fn test_cgroup_usage_bar_colors() {
+        let gib = 1024 * 1024 * 1024;
+        let memory = MemoryPollResult {
+            total: 16 * gib,
+            ..Default::default()
+        };
+        let mut cgroup = CgroupLimits {
+            version: CgroupVersion::V2,
+            path: "/".to_string(),
+            memory_limit: Some(8 * gib),
+            memory_usage: Some(gib),
+            cpu_quota: None,
+            cpuset: vec![],
+            cpu_usage: None,
+        };
+
+        let colors = |cgroup: &CgroupLimits| -> Vec<Option<Color>> {
+            memory_lines(&memory, Some(cgroup), 26)[2]
+                .spans
+                .iter()
+                .filter(|s| s.content == "|" && s.style.fg != Some(Color::DarkGray))
+                .map(|s| s.style.fg)
+                .collect()
+        };
+
+        // Usage far below a limit in bytes is green, not red.
+        let low = colors(&cgroup);
+        assert!(!low.is_empty() && low.iter().all(|c| *c == Some(Color::Green)));
+
+        cgroup.memory_usage = Some(8 * gib);
+
+        let full = colors(&cgroup);
+        assert!(full.first() == Some(&Some(Color::Green)));
+        assert!(full.last() == Some(&Some(Color::Red)));
+    }
+}
@@ -1,7 +1,16 @@
 pub mod bar_chart;
+pub mod battery;
+pub mod cgroups;
 pub mod cpu;
+pub mod disk;
+pub mod energy;
 pub mod gpu;
 pub mod memory;
+pub mod network;
+pub mod popup;
+pub mod pressure;
+pub mod process;
+pub mod sensors;
 pub mod state;
 pub mod ui;
 pub mod util;
@@ -0,0 +1,228 @@
+// Contains functionality for drawing ui elements related to network reporting.
+use human_bytes::human_bytes;
+use ratatui::{
+    layout::{Constraint, Direction, Layout, Rect},
+    style::{Color, Style, Stylize},
+    text::{Line, Span},
+    widgets::{
+        block::{Position, Title},
+        Block, BorderType, Borders, Padding, Paragraph, Sparkline,
+    },
+    Frame,
+};
+
+use crate::monitoring::{history::PollHistory, network::NetworkPollResult};
+
+use super::bar_chart::generate_bar_chart;
+
+/// Rows taken up by each interface in the network block.
+const INTERFACE_HEIGHT: u16 = 3;
+
+/// Select the interfaces to show. Loopback and virtual interfaces are only
+/// shown if `show_all` is set.
+pub fn visible_interfaces(
+    interfaces: &[NetworkPollResult],
+    show_all: bool,
+) -> Vec<&NetworkPollResult> {
+    interfaces
+        .iter()
+        .filter(|i| show_all || !(i.is_loopback || i.is_virtual))
+        .collect()
+}
+
+/// Height the network block needs to show every visible interface.
+pub fn network_block_height(interfaces: &[NetworkPollResult], show_all: bool) -> u16 {
+    visible_interfaces(interfaces, show_all).len().max(1) as u16 * INTERFACE_HEIGHT + 2
+}
+
+/// Draws a blocked element with the state, current throughput, and recent
+/// throughput history of each network interface.
+pub fn draw_network_block(poll_data: &PollHistory, show_all: bool, f: &mut Frame, area: Rect) {
+    let hint = match show_all {
+        true => " i: Hide virtual interfaces ",
+        false => " i: Show all interfaces ",
+    };
+
+    let block = Block::default()
+        .title(" Network ")
+        .title(Title::from(hint).position(Position::Bottom))
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded)
+        .padding(Padding::new(1, 1, 0, 0));
+
+    let inner = block.inner(area);
+
+    f.render_widget(block, area);
+
+    let interfaces = visible_interfaces(&poll_data.latest().network, show_all);
+
+    if interfaces.is_empty() {
+        f.render_widget(Paragraph::new("No network interfaces found.").dim(), inner);
+        return;
+    }
+
+    let l = Layout::default()
+        .constraints(vec![Constraint::Length(INTERFACE_HEIGHT); interfaces.len()])
+        .split(inner);
+
+    for (interface, area) in interfaces.iter().zip(l.iter()) {
+        // Rates of this interface, from oldest to newest.
+        let history: Vec<(f64, f64)> = poll_data
+            .network()
+            .map(|network| {
+                network
+                    .iter()
+                    .find(|i| i.name == interface.name)
+                    .map_or((0f64, 0f64), |i| (i.rates.rx_bytes, i.rates.tx_bytes))
+            })
+            .collect();
+
+        draw_interface(interface, &history, f, *area);
+    }
+}
+
+/// Draws the state of a single interface on one line, followed by a
+/// throughput bar, and history graph for each direction.
+fn draw_interface(
+    interface: &NetworkPollResult,
+    history: &[(f64, f64)],
+    f: &mut Frame,
+    area: Rect,
+) {
+    let state_color = match interface.operstate.as_str() {
+        "up" => Color::Green,
+        "down" => Color::Red,
+        _ => Color::Yellow,
+    };
+
+    let mut spans = vec![
+        Span::styled(interface.name.clone(), Style::new().bold()),
+        Span::raw(" "),
+        Span::styled(interface.operstate.clone(), Style::new().fg(state_color)),
+    ];
+
+    if let Some(speed) = interface.link_speed {
+        spans.push(Span::raw(format!("  {} Mb/s", speed)));
+    }
+
+    let (errors, dropped) = (
+        interface.totals.rx_errors + interface.totals.tx_errors,
+        interface.totals.rx_dropped + interface.totals.tx_dropped,
+    );
+
+    if errors + dropped > 0 {
+        spans.push(Span::styled(
+            format!("  err {}  drop {}", errors, dropped),
+            Style::new().red(),
+        ));
+    }
+
+    if !interface.addresses.is_empty() {
+        let addresses: Vec<String> = interface.addresses.iter().map(|a| a.to_string()).collect();
+        spans.push(Span::styled(
+            format!("  {}", addresses.join(", ")),
+            Style::new().dim(),
+        ));
+    }
+
+    let l = Layout::default()
+        .constraints(vec![Constraint::Length(1); 3])
+        .split(area);
+
+    f.render_widget(Paragraph::new(Line::from(spans)), l[0]);
+
+    // Throughput bars are shown relative to the link speed, or to the
+    // busiest recent poll when the link speed is unknown. History graphs
+    // always use the busiest recent poll, so that light traffic is visible.
+    let link_bytes = interface.link_speed.map(|s| s as f64 * 125_000f64);
+
+    let directions = [
+        (
+            "rx",
+            interface.rates.rx_bytes,
+            history.iter().map(|h| h.0).collect::<Vec<_>>(),
+        ),
+        (
+            "tx",
+            interface.rates.tx_bytes,
+            history.iter().map(|h| h.1).collect::<Vec<_>>(),
+        ),
+    ];
+
+    for ((direction, rate, history), row) in directions.into_iter().zip(l.iter().skip(1)) {
+        let history_max = history.iter().copied().fold(0f64, f64::max).max(1f64);
+        let max = link_bytes.unwrap_or(history_max).max(1f64);
+
+        let cols = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints(vec![
+                Constraint::Percentage(50),
+                Constraint::Length(1),
+                Constraint::Percentage(50),
+            ])
+            .split(*row);
+
+        let label = format!("{} {}/s", direction, human_bytes(rate));
+
+        f.render_widget(
+            Paragraph::new(*generate_bar_chart(
+                &label,
+                (100f64 * rate / max).min(100f64) as f32,
+                (0f32, 100f32),
+                18,
+                cols[0].width as usize,
+            )),
+            cols[0],
+        );
+
+        // Sparklines draw their data from the left, so only the most recent
+        // readings which fit are kept.
+        let data: Vec<u64> = history
+            .iter()
+            .skip(history.len().saturating_sub(cols[2].width as usize))
+            .map(|r| r.round() as u64)
+            .collect();
+
+        f.render_widget(
+            Sparkline::default()
+                .data(&data)
+                .max(history_max.round() as u64)
+                .style(Style::new().cyan()),
+            cols[2],
+        );
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+
+    fn interface(name: &str, is_loopback: bool, is_virtual: bool) -> NetworkPollResult {
+        NetworkPollResult {
+            name: name.to_string(),
+            is_loopback,
+            is_virtual,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_visible_interfaces() {
+        let interfaces = [
+            interface("docker0", false, true),
+            interface("eth0", false, false),
+            interface("lo", true, true),
+        ];
+
+        let names = |show_all| {
+            visible_interfaces(&interfaces, show_all)
+                .iter()
+                .map(|i| i.name.as_str())
+                .collect::<Vec<_>>()
+        };
+
+        assert!(names(false) == ["eth0"]);
+        assert!(names(true) == ["docker0", "eth0", "lo"]);
+        assert!(network_block_height(&interfaces, false) == 5);
+    }
+}
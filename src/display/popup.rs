@@ -0,0 +1,108 @@
+// Contains functionality for drawing popups over the current tab.
+use ratatui::{
+    layout::{Alignment, Constraint, Direction, Layout, Rect},
+    style::{Color, Style, Stylize},
+    text::{Line, Span},
+    widgets::{Block, BorderType, Borders, Clear, Padding, Paragraph, Wrap},
+    Frame,
+};
+
+use super::state::Popup;
+
+/// Draws the given popup centered in `area`.
+pub fn draw_popup(popup: &Popup, f: &mut Frame, area: Rect) {
+    let (title, border_color, mut text) = match popup {
+        Popup::Confirm(action) => (
+            " Confirm ",
+            Color::Yellow,
+            vec![Line::from(format!("{}?", action.describe()))],
+        ),
+        Popup::Renice { pid, name, nice } => (
+            " Renice ",
+            Color::White,
+            vec![
+                Line::from(format!("{} ({})", name, pid)),
+                Line::from(""),
+                Line::from(vec![
+                    Span::raw("Nice value: "),
+                    Span::styled(format!("< {} >", nice), Style::new().bold()),
+                ]),
+            ],
+        ),
+        Popup::Affinity {
+            pid,
+            name,
+            cpus,
+            cursor,
+        } => (
+            " CPU Affinity ",
+            Color::White,
+            vec![
+                Line::from(format!("{} ({})", name, pid)),
+                Line::from(""),
+                Line::from(
+                    cpus.iter()
+                        .enumerate()
+                        .map(|(i, enabled)| {
+                            let style = match (i == *cursor, enabled) {
+                                (true, _) => Style::new().reversed(),
+                                (false, true) => Style::new().green(),
+                                (false, false) => Style::new().dark_gray(),
+                            };
+
+                            Span::styled(format!(" {} ", i), style)
+                        })
+                        .collect::<Vec<_>>(),
+                ),
+            ],
+        ),
+        Popup::Error(message) => (" Error ", Color::Red, vec![Line::from(message.clone())]),
+    };
+
+    let hints = match popup {
+        Popup::Confirm(_) => "y: Confirm  n: Cancel",
+        Popup::Renice { .. } => "+/-: Change  Enter: Apply  Esc: Cancel",
+        Popup::Affinity { .. } => "Space: Toggle  a: All  Enter: Apply  Esc: Cancel",
+        Popup::Error(_) => "Press any key to dismiss",
+    };
+
+    text.push(Line::from(""));
+    text.push(Line::styled(hints, Style::new().dim()));
+
+    let popup_area = centered_rect(60, text.len() as u16 + 4, area);
+
+    let p = Paragraph::new(text)
+        .alignment(Alignment::Center)
+        .wrap(Wrap { trim: true })
+        .block(
+            Block::default()
+                .title(title)
+                .borders(Borders::ALL)
+                .border_type(BorderType::Rounded)
+                .border_style(Style::new().fg(border_color))
+                .padding(Padding::new(1, 1, 1, 0)),
+        );
+
+    f.render_widget(Clear, popup_area);
+    f.render_widget(p, popup_area);
+}
+
+/// Compute a rect of the given width (as a percentage of `area`) and height
+/// (in rows), centered in `area`.
+fn centered_rect(percent_x: u16, height: u16, area: Rect) -> Rect {
+    let height = height.min(area.height);
+    let vertical = Rect {
+        y: area.y + (area.height - height) / 2,
+        height,
+        ..area
+    };
+
+    Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints(vec![
+            Constraint::Percentage((100 - percent_x) / 2),
+            Constraint::Percentage(percent_x),
+            Constraint::Percentage((100 - percent_x) / 2),
+        ])
+        .split(vertical)[1]
+}
@@ -0,0 +1,128 @@
+// Contains functionality for drawing ui elements related to pressure stall
+// information.
+use ratatui::{
+    layout::Rect,
+    style::{Style, Stylize},
+    text::{Line, Span},
+    widgets::{Padding, Paragraph},
+    Frame,
+};
+
+use crate::monitoring::pressure::{PressureAverages, PressurePollResult};
+
+use super::{bar_chart::generate_bar_chart, util::default_block};
+
+/// Characters taken up by row names, such as `memory full`.
+const NAME_WIDTH: usize = 12;
+
+/// Characters taken up by the percentage in front of each bar.
+const VALUE_WIDTH: usize = 7;
+
+/// Each row of the pressure block, as the name of the row, and the averages
+/// it shows. `full` rows are only present for resources which report them.
+fn pressure_rows(pressure: &PressurePollResult) -> Vec<(String, PressureAverages)> {
+    pressure
+        .resources()
+        .into_iter()
+        .flat_map(|(name, resource)| {
+            [("some", Some(resource.some)), ("full", resource.full)]
+                .into_iter()
+                .filter_map(move |(kind, averages)| Some((format!("{} {}", name, kind), averages?)))
+        })
+        .collect()
+}
+
+/// Get the height of the pressure block, including borders.
+pub fn pressure_block_height(pressure: &PressurePollResult) -> u16 {
+    pressure_rows(pressure).len().max(1) as u16 + 2
+}
+
+/// Draws a block with the share of time tasks were stalled on cpu, memory,
+/// and io, as one bar for each of the 10, 60, and 300 second averages.
+pub fn draw_pressure_block(pressure: &PressurePollResult, title: &str, f: &mut Frame, area: Rect) {
+    let block = default_block(title).padding(Padding::new(1, 1, 0, 0));
+
+    let rows = pressure_rows(pressure);
+
+    if rows.is_empty() {
+        f.render_widget(
+            Paragraph::new("Pressure stall information is not available.")
+                .dim()
+                .block(block),
+            area,
+        );
+        return;
+    }
+
+    let bar_width = (block.inner(area).width as usize).saturating_sub(NAME_WIDTH) / 3;
+
+    // Labels are collected first, as the bars borrow them.
+    let labels: Vec<[String; 3]> = rows
+        .iter()
+        .map(|(_, a)| [a.avg10, a.avg60, a.avg300].map(|v| format!("{:.1}%", v)))
+        .collect();
+
+    let lines: Vec<Line> = rows
+        .iter()
+        .zip(labels.iter())
+        .map(|((name, averages), labels)| {
+            let mut spans = vec![Span::styled(
+                format!("{:<width$}", name, width = NAME_WIDTH),
+                Style::new().bold(),
+            )];
+
+            let values = [averages.avg10, averages.avg60, averages.avg300];
+
+            for (label, value) in labels.iter().zip(values) {
+                spans.extend(
+                    generate_bar_chart(
+                        label,
+                        value.clamp(0f32, 100f32),
+                        (0f32, 100f32),
+                        VALUE_WIDTH,
+                        bar_width.saturating_sub(1),
+                    )
+                    .spans
+                    .clone(),
+                );
+                spans.push(Span::raw(" "));
+            }
+
+            Line::from(spans)
+        })
+        .collect();
+
+    f.render_widget(Paragraph::new(lines).block(block), area);
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+    use crate::monitoring::pressure::ResourcePressure;
+
+    #[test]
+    fn test_pressure_rows() {
+        let averages = PressureAverages {
+            avg10: 12.5,
+            ..Default::default()
+        };
+
+        let pressure = PressurePollResult {
+            cpu: Some(ResourcePressure {
+                some: averages,
+                full: None,
+            }),
+            memory: None,
+            io: Some(ResourcePressure {
+                some: averages,
+                full: Some(averages),
+            }),
+        };
+
+        let names: Vec<String> = pressure_rows(&pressure).into_iter().map(|r| r.0).collect();
+
+        assert!(names == ["cpu some", "io some", "io full"]);
+        assert!(pressure_block_height(&pressure) == 5);
+        assert!(pressure_block_height(&PressurePollResult::default()) == 3);
+    }
+}
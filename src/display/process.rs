@@ -0,0 +1,323 @@
+// Contains functionality for drawing ui elements related to process reporting.
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use human_bytes::human_bytes;
+use ratatui::{
+    layout::{Constraint, Rect},
+    style::{Color, Style, Stylize},
+    text::Span,
+    widgets::{
+        block::{Position, Title},
+        Block, BorderType, Borders, Cell, Row, Table, TableState,
+    },
+    Frame,
+};
+
+use crate::monitoring::process::{
+    build_process_tree, group_name, group_processes, sort_processes, ProcessPollResult,
+    ProcessSortColumn, ProcessTreeRow,
+};
+
+use super::{
+    state::{ProcessTableState, ProcessView},
+    util::get_color_for_range,
+};
+
+/// A single row of the process table.
+enum TableRow {
+    Process(ProcessTreeRow),
+    /// Header of a group of processes with the same owner, with their count,
+    /// and totals.
+    Group {
+        name: String,
+        count: usize,
+        cpu_usage: f32,
+        memory: u64,
+        collapsed: bool,
+    },
+}
+
+/// Lay processes out in rows, grouped by owner. Processes are listed below
+/// the header of their group, unless the group is collapsed.
+fn grouped_rows(processes: &[ProcessPollResult], state: &ProcessTableState) -> Vec<TableRow> {
+    group_processes(processes, state.sort_column, state.sort_descending)
+        .into_iter()
+        .flat_map(|g| {
+            let name = g.name();
+            let collapsed = state.collapsed_groups.contains(&name);
+
+            let header = TableRow::Group {
+                count: g.processes.len(),
+                cpu_usage: g.cpu_usage,
+                memory: g.memory,
+                name,
+                collapsed,
+            };
+
+            let members = g
+                .processes
+                .into_iter()
+                .filter(move |_| !collapsed)
+                .map(|p| {
+                    TableRow::Process(ProcessTreeRow {
+                        subtree_cpu_usage: p.cpu_usage,
+                        subtree_memory: p.memory,
+                        process: p,
+                        depth: 1,
+                        has_children: false,
+                        collapsed: false,
+                    })
+                });
+
+            std::iter::once(header).chain(members)
+        })
+        .collect()
+}
+
+/// Draws a scrollable table of running processes.
+///
+/// Processes are sorted according to the column and direction stored in the
+/// given `state`, and the selected row and scroll offset are written back to
+/// it so that they persist between draws.
+///
+/// In tree view, processes are nested below their parents, and two extra
+/// columns show the cpu and memory totals of each subtree. In grouped view,
+/// processes are listed below the owner they belong to, whose header row
+/// shows the totals of the group.
+pub fn draw_process_table(
+    processes: &[ProcessPollResult],
+    state: &mut ProcessTableState,
+    f: &mut Frame,
+    area: Rect,
+) {
+    // A process linked from another tab may be hidden in a folded subtree,
+    // so all of its ancestors are unfolded. The walk is bounded, as parent
+    // links may contain cycles.
+    if let (Some(pid), ProcessView::Tree) = (state.focus_pid, state.view) {
+        let parent_of = |pid: u32| {
+            processes
+                .iter()
+                .find(|p| p.pid == pid)
+                .and_then(|p| p.parent_pid)
+        };
+
+        let mut parent = parent_of(pid);
+
+        for _ in 0..processes.len() {
+            let Some(ppid) = parent else {
+                break;
+            };
+
+            state.collapsed.remove(&ppid);
+            parent = parent_of(ppid);
+        }
+    }
+
+    // Likewise, the group of a linked process is unfolded.
+    if let (Some(pid), ProcessView::Grouped) = (state.focus_pid, state.view) {
+        if let Some(p) = processes.iter().find(|p| p.pid == pid) {
+            state.collapsed_groups.remove(&group_name(p.owner.as_ref()));
+        }
+    }
+
+    let rows: Vec<TableRow> = match state.view {
+        ProcessView::Tree => build_process_tree(
+            processes,
+            state.sort_column,
+            state.sort_descending,
+            &state.collapsed,
+        )
+        .into_iter()
+        .map(TableRow::Process)
+        .collect(),
+        ProcessView::Grouped => grouped_rows(processes, state),
+        ProcessView::List => {
+            let mut processes = processes.to_vec();
+            sort_processes(&mut processes, state.sort_column, state.sort_descending);
+
+            processes
+                .into_iter()
+                .map(|p| {
+                    TableRow::Process(ProcessTreeRow {
+                        subtree_cpu_usage: p.cpu_usage,
+                        subtree_memory: p.memory,
+                        process: p,
+                        depth: 0,
+                        has_children: false,
+                        collapsed: false,
+                    })
+                })
+                .collect()
+        }
+    };
+
+    if let Some(pid) = state.focus_pid.take() {
+        let focused = |r: &TableRow| matches!(r, TableRow::Process(r) if r.process.pid == pid);
+
+        if let Some(i) = rows.iter().position(focused) {
+            state.selected = i;
+        }
+    }
+
+    state.selected = state.selected.min(rows.len().saturating_sub(1));
+    (state.selected_process, state.selected_group) = match rows.get(state.selected) {
+        Some(TableRow::Process(r)) => (Some((r.process.pid, r.process.name.clone())), None),
+        Some(TableRow::Group { name, .. }) => (None, Some(name.clone())),
+        None => (None, None),
+    };
+
+    let mut titles: Vec<String> = ProcessSortColumn::ALL
+        .iter()
+        .map(|c| match *c == state.sort_column {
+            true if state.sort_descending => format!("{} ▼", c.title()),
+            true => format!("{} ▲", c.title()),
+            false => c.title().to_string(),
+        })
+        .collect();
+
+    let mut widths = vec![
+        Constraint::Length(8),
+        Constraint::Percentage(30),
+        Constraint::Length(12),
+        Constraint::Length(24),
+        Constraint::Length(7),
+        Constraint::Length(10),
+        Constraint::Length(10),
+        Constraint::Length(12),
+    ];
+
+    if state.view == ProcessView::Tree {
+        titles.extend(["Σ CPU %".to_string(), "Σ RSS".to_string()]);
+        widths.extend([Constraint::Length(8), Constraint::Length(10)]);
+    }
+
+    let header = Row::new(titles).style(Style::new().bold()).bottom_margin(1);
+
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+
+    let fold_marker = |has_children: bool, collapsed: bool| match (has_children, collapsed) {
+        (true, true) => "▸ ",
+        (true, false) => "▾ ",
+        (false, _) => "  ",
+    };
+
+    let table_rows = rows.iter().map(|r| {
+        let r = match r {
+            TableRow::Process(r) => r,
+            TableRow::Group {
+                name,
+                count,
+                cpu_usage,
+                memory,
+                collapsed,
+            } => {
+                return Row::new(vec![
+                    Cell::from(""),
+                    Cell::from(format!("{}{}", fold_marker(true, *collapsed), name)),
+                    Cell::from(""),
+                    Cell::from(""),
+                    Cell::from(Span::styled(
+                        format!("{:.1}", cpu_usage),
+                        Style::new().fg(get_color_for_range(*cpu_usage, (0f32, 100f32))),
+                    )),
+                    Cell::from(human_bytes(*memory as f64)),
+                    Cell::from(format!("{} procs", count)),
+                ])
+                .style(Style::new().bold());
+            }
+        };
+
+        let p = &r.process;
+
+        let name = match state.view {
+            ProcessView::List => p.name.clone(),
+            _ => format!(
+                "{}{}{}",
+                "  ".repeat(r.depth),
+                fold_marker(r.has_children, r.collapsed),
+                p.name
+            ),
+        };
+
+        let owner = p.owner.as_ref().map(|o| o.to_string()).unwrap_or_default();
+
+        let mut cells = vec![
+            Cell::from(p.pid.to_string()),
+            Cell::from(name),
+            Cell::from(p.user.clone()),
+            Cell::from(owner),
+            Cell::from(Span::styled(
+                format!("{:.1}", p.cpu_usage),
+                Style::new().fg(get_color_for_range(p.cpu_usage, (0f32, 100f32))),
+            )),
+            Cell::from(human_bytes(p.memory as f64)),
+            Cell::from(p.state.clone()),
+            Cell::from(format_elapsed(now.saturating_sub(p.start_time))),
+        ];
+
+        if state.view == ProcessView::Tree {
+            cells.push(Cell::from(Span::styled(
+                format!("{:.1}", r.subtree_cpu_usage),
+                Style::new().fg(get_color_for_range(r.subtree_cpu_usage, (0f32, 100f32))),
+            )));
+            cells.push(Cell::from(human_bytes(r.subtree_memory as f64)));
+        }
+
+        Row::new(cells)
+    });
+
+    let hints = match state.view {
+        ProcessView::Tree => " </>: Sort  r: Reverse  v: Flat view  g: Group  Space: Fold  t/K/z/c: Signal  n: Nice  a: Affinity ",
+        ProcessView::Grouped => " </>: Sort  r: Reverse  g: Ungroup  Space: Fold  t/K/z/c: Signal  n: Nice  a: Affinity ",
+        ProcessView::List => " </>: Sort  r: Reverse  v: Tree view  g: Group  t: Term  K: Kill  z: Stop  c: Cont  n: Nice  a: Affinity ",
+    };
+
+    let group_count = rows
+        .iter()
+        .filter(|r| matches!(r, TableRow::Group { .. }))
+        .count();
+
+    let title = match state.view {
+        ProcessView::Grouped => format!(
+            " Processes ({} in {} groups) ",
+            processes.len(),
+            group_count
+        ),
+        _ => format!(" Processes ({}) ", processes.len()),
+    };
+
+    let table = Table::new(table_rows, widths)
+        .header(header)
+        .block(
+            Block::default()
+                .title(title)
+                .title(Title::from(hints).position(Position::Bottom))
+                .borders(Borders::ALL)
+                .border_type(BorderType::Rounded),
+        )
+        .highlight_style(Style::new().bg(Color::DarkGray).bold());
+
+    let mut table_state = TableState::default()
+        .with_offset(state.offset)
+        .with_selected(Some(state.selected));
+
+    f.render_stateful_widget(table, area, &mut table_state);
+
+    state.offset = table_state.offset();
+}
+
+/// Format a duration in seconds as a short human readable string, such as
+/// `3d 04h` or `12m 05s`.
+fn format_elapsed(secs: u64) -> String {
+    let (d, h, m, s) = (secs / 86400, secs / 3600 % 24, secs / 60 % 60, secs % 60);
+
+    match (d, h, m) {
+        (0, 0, 0) => format!("{}s", s),
+        (0, 0, _) => format!("{}m {:02}s", m, s),
+        (0, _, _) => format!("{}h {:02}m", h, m),
+        _ => format!("{}d {:02}h", d, h),
+    }
+}
@@ -0,0 +1,183 @@
+// Contains functionality for drawing ui elements related to hardware sensor
+// reporting.
+use ratatui::{
+    layout::Rect,
+    style::{Style, Stylize},
+    text::{Line, Span},
+    widgets::{
+        block::{Position, Title},
+        Padding, Paragraph,
+    },
+    Frame,
+};
+
+use crate::monitoring::sensors::{Sensor, SensorChip, SensorKind};
+
+use super::{bar_chart::generate_bar_chart, state::SensorsTabState, util::default_block};
+
+/// Characters taken up by sensor labels.
+const LABEL_WIDTH: usize = 20;
+
+/// Characters taken up by sensor values.
+const VALUE_WIDTH: usize = 12;
+
+/// Characters taken up by the bar of sensors which report a limit.
+const BAR_WIDTH: usize = 40;
+
+/// Format a value of the given sensor kind, with its unit.
+fn format_value(kind: SensorKind, value: f64) -> String {
+    match kind {
+        SensorKind::Temperature => format!("{:.1}{}", value, kind.unit()),
+        SensorKind::Fan => format!("{:.0} {}", value, kind.unit()),
+        SensorKind::Voltage => format!("{:.3}{}", value, kind.unit()),
+        SensorKind::Power => format!("{:.1}{}", value, kind.unit()),
+    }
+}
+
+/// Build the line of a single sensor, with its label, value, a bar bounded
+/// by its limit, and its max and critical thresholds.
+fn sensor_line(sensor: &Sensor) -> Line<'static> {
+    let mut spans = vec![
+        Span::raw(format!(
+            "  {:<width$}",
+            sensor.label,
+            width = LABEL_WIDTH - 2
+        )),
+        Span::styled(
+            format!(
+                "{:>width$}  ",
+                format_value(sensor.kind, sensor.value),
+                width = VALUE_WIDTH
+            ),
+            Style::new().bold(),
+        ),
+    ];
+
+    match sensor.upper_bound() {
+        Some(bound) => spans.extend(
+            generate_bar_chart(
+                "",
+                sensor.value.min(bound) as f32,
+                (0f32, bound as f32),
+                0,
+                BAR_WIDTH,
+            )
+            .spans
+            .clone(),
+        ),
+        None => spans.push(Span::raw(" ".repeat(BAR_WIDTH))),
+    }
+
+    let thresholds: Vec<String> = [("max", sensor.max), ("crit", sensor.critical)]
+        .into_iter()
+        .filter_map(|(name, t)| Some(format!("{} {}", name, format_value(sensor.kind, t?))))
+        .collect();
+
+    if !thresholds.is_empty() {
+        spans.push(Span::styled(
+            format!("  {}", thresholds.join("  ")),
+            Style::new().dim(),
+        ));
+    }
+
+    Line::from(spans)
+}
+
+/// Build the lines of the sensors tab, with a header for each chip, followed
+/// by its sensors.
+fn sensor_lines(chips: &[SensorChip]) -> Vec<Line<'static>> {
+    let mut lines = vec![];
+
+    for (i, chip) in chips.iter().enumerate() {
+        if i > 0 {
+            lines.push(Line::default());
+        }
+
+        lines.push(Line::styled(chip.name.clone(), Style::new().bold().cyan()));
+        lines.extend(chip.sensors.iter().map(sensor_line));
+    }
+
+    lines
+}
+
+/// Draws the sensors tab, which lists every sensor of every hardware
+/// monitoring chip, grouped by chip.
+///
+/// When there are more sensors than fit, the list is scrolled to start at
+/// the offset in `state`.
+pub fn draw_sensors_tab(
+    chips: &[SensorChip],
+    state: &mut SensorsTabState,
+    f: &mut Frame,
+    area: Rect,
+) {
+    let lines = sensor_lines(chips);
+
+    let mut block = default_block(" Sensors ").padding(Padding::new(1, 1, 0, 0));
+    let visible = block.inner(area).height as usize;
+
+    state.offset = state.offset.min(lines.len().saturating_sub(visible));
+
+    if lines.len() > visible {
+        block = block.title(Title::from(" ↑/↓: Scroll ").position(Position::Bottom));
+    }
+
+    if chips.is_empty() {
+        f.render_widget(
+            Paragraph::new("No hardware sensors found.")
+                .dim()
+                .block(block),
+            area,
+        );
+        return;
+    }
+
+    f.render_widget(
+        Paragraph::new(lines)
+            .block(block)
+            .scroll((state.offset as u16, 0)),
+        area,
+    );
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sensor_lines() {
+        let chips = [
+            SensorChip {
+                name: "coretemp".to_string(),
+                sensors: vec![Sensor {
+                    kind: SensorKind::Temperature,
+                    label: "Package id 0".to_string(),
+                    value: 52.0,
+                    max: Some(80.0),
+                    critical: Some(100.0),
+                }],
+            },
+            SensorChip {
+                name: "nct6798".to_string(),
+                sensors: vec![Sensor {
+                    kind: SensorKind::Fan,
+                    label: "fan1".to_string(),
+                    value: 1200.0,
+                    max: None,
+                    critical: None,
+                }],
+            },
+        ];
+
+        let text: Vec<String> = sensor_lines(&chips)
+            .iter()
+            .map(|l| l.spans.iter().map(|s| s.content.as_ref()).collect())
+            .collect();
+
+        assert!(text.len() == 5);
+        assert!(text[0] == "coretemp");
+        assert!(text[1].contains("52.0C") && text[1].ends_with("max 80.0C  crit 100.0C"));
+        assert!(text[3] == "nct6798");
+        assert!(text[4].contains("1200 RPM"));
+    }
+}
@@ -1,17 +1,505 @@
+use std::collections::HashSet;
 use std::sync::{Arc, Mutex};
 
+use crossterm::event::{KeyCode, KeyEvent};
+
+use crate::monitoring::actions::{self, ProcessAction, ProcessSignal};
+use crate::monitoring::energy::{EnergyRates, SessionEnergy};
+use crate::monitoring::process::ProcessSortColumn;
+
+/// Enumerates the tabs which can be selected in the ui.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Tab {
+    Home,
+    Processes,
+    Gpu,
+    Disks,
+    Sensors,
+    Cgroups,
+}
+
+impl Tab {
+    /// All tabs, in the order in which they are displayed in the header.
+    pub const ALL: [Tab; 6] = [
+        Tab::Home,
+        Tab::Processes,
+        Tab::Gpu,
+        Tab::Disks,
+        Tab::Sensors,
+        Tab::Cgroups,
+    ];
+
+    /// Human readable tab title.
+    pub fn title(&self) -> &'static str {
+        match self {
+            Tab::Home => "Home",
+            Tab::Processes => "Processes",
+            Tab::Gpu => "GPU",
+            Tab::Disks => "Disks",
+            Tab::Sensors => "Sensors",
+            Tab::Cgroups => "Cgroups",
+        }
+    }
+
+    /// Index of this tab in [`Tab::ALL`].
+    pub fn index(&self) -> usize {
+        Self::ALL.iter().position(|t| t == self).unwrap_or(0)
+    }
+}
+
+/// Enumerates the popups which can be drawn over the current tab.
+///
+/// While a popup is open, it receives all key events.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Popup {
+    /// Ask the user to confirm an action before it is executed.
+    Confirm(ProcessAction),
+    /// Choose a new nice value for a process.
+    Renice { pid: u32, name: String, nice: i32 },
+    /// Choose the set of cpus a process may run on.
+    Affinity {
+        pid: u32,
+        name: String,
+        cpus: Vec<bool>,
+        cursor: usize,
+    },
+    /// Report an error to the user.
+    Error(String),
+}
+
+impl Popup {
+    /// Handle a key event directed at this popup.
+    ///
+    /// Returns the popup which should be shown after this key event, if any,
+    /// and an action, if the user has confirmed one.
+    fn handle_key(self, key: KeyEvent) -> (Option<Popup>, Option<ProcessAction>) {
+        match (self, key.code) {
+            (_, KeyCode::Esc) => (None, None),
+            (Popup::Error(_), _) => (None, None),
+            (Popup::Confirm(action), KeyCode::Char('y') | KeyCode::Enter) => (None, Some(action)),
+            (Popup::Confirm(_), KeyCode::Char('n')) => (None, None),
+            (Popup::Renice { pid, name, nice }, code) => match code {
+                KeyCode::Up | KeyCode::Char('+') | KeyCode::Char('k') => {
+                    let nice = (nice + 1).min(19);
+                    (Some(Popup::Renice { pid, name, nice }), None)
+                }
+                KeyCode::Down | KeyCode::Char('-') | KeyCode::Char('j') => {
+                    let nice = (nice - 1).max(-20);
+                    (Some(Popup::Renice { pid, name, nice }), None)
+                }
+                KeyCode::Enter => (Some(Popup::Confirm(ProcessAction::Renice(pid, nice))), None),
+                _ => (Some(Popup::Renice { pid, name, nice }), None),
+            },
+            (
+                Popup::Affinity {
+                    pid,
+                    name,
+                    mut cpus,
+                    mut cursor,
+                },
+                code,
+            ) => {
+                match code {
+                    KeyCode::Up | KeyCode::Left | KeyCode::Char('k') => {
+                        cursor = cursor.saturating_sub(1)
+                    }
+                    KeyCode::Down | KeyCode::Right | KeyCode::Char('j') => {
+                        cursor = (cursor + 1).min(cpus.len().saturating_sub(1))
+                    }
+                    KeyCode::Char(' ') => {
+                        if let Some(c) = cpus.get_mut(cursor) {
+                            *c = !*c;
+                        }
+                    }
+                    KeyCode::Char('a') => {
+                        let all = cpus.iter().all(|c| *c);
+                        cpus.iter_mut().for_each(|c| *c = !all);
+                    }
+                    KeyCode::Enter => {
+                        let selected = cpus
+                            .iter()
+                            .enumerate()
+                            .filter(|(_, c)| **c)
+                            .map(|(i, _)| i)
+                            .collect();
+
+                        return (
+                            Some(Popup::Confirm(ProcessAction::SetAffinity(pid, selected))),
+                            None,
+                        );
+                    }
+                    _ => (),
+                };
+
+                (
+                    Some(Popup::Affinity {
+                        pid,
+                        name,
+                        cpus,
+                        cursor,
+                    }),
+                    None,
+                )
+            }
+            (popup, _) => (Some(popup), None),
+        }
+    }
+}
+
+/// Enumerates the ways the process table can arrange processes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProcessView {
+    /// Every process, ordered by the sort column.
+    List,
+    /// Processes nested below their parents.
+    Tree,
+    /// Processes grouped below the systemd unit, container, or pod they
+    /// belong to.
+    Grouped,
+}
+
+/// Contains the ui state of the process table, which must persist between
+/// draw calls.
+pub struct ProcessTableState {
+    pub(crate) sort_column: ProcessSortColumn,
+    pub(crate) sort_descending: bool,
+    pub(crate) selected: usize,
+    pub(crate) offset: usize,
+    pub(crate) view: ProcessView,
+    /// Pids of processes whose children are hidden in the tree view.
+    pub(crate) collapsed: HashSet<u32>,
+    /// Names of the groups whose processes are hidden in the grouped view.
+    pub(crate) collapsed_groups: HashSet<String>,
+    /// Pid and name of the process in the selected row, as of the last draw.
+    pub(crate) selected_process: Option<(u32, String)>,
+    /// Name of the group in the selected row, as of the last draw, if the
+    /// selected row is the header of a group.
+    pub(crate) selected_group: Option<String>,
+    /// Pid of a process to select on the next draw, set when another tab
+    /// links to a process.
+    pub(crate) focus_pid: Option<u32>,
+}
+
+impl ProcessTableState {
+    fn new() -> Self {
+        ProcessTableState {
+            sort_column: ProcessSortColumn::CpuUsage,
+            sort_descending: true,
+            selected: 0,
+            offset: 0,
+            view: ProcessView::List,
+            collapsed: HashSet::new(),
+            collapsed_groups: HashSet::new(),
+            selected_process: None,
+            selected_group: None,
+            focus_pid: None,
+        }
+    }
+
+    /// Handle a key event directed at the process table.
+    ///
+    /// Returns a popup to open, if the key requests an action on the
+    /// selected process.
+    fn handle_key(&mut self, key: KeyEvent) -> Option<Popup> {
+        if let Some(group) = self.selected_group.clone() {
+            match key.code {
+                KeyCode::Char(' ') => {
+                    match self.collapsed_groups.contains(&group) {
+                        true => self.collapsed_groups.remove(&group),
+                        false => self.collapsed_groups.insert(group),
+                    };
+                }
+                KeyCode::Left => {
+                    self.collapsed_groups.insert(group);
+                }
+                KeyCode::Right => {
+                    self.collapsed_groups.remove(&group);
+                }
+                _ => (),
+            }
+        }
+
+        if let Some((pid, name)) = self.selected_process.clone() {
+            let tree_view = self.view == ProcessView::Tree;
+
+            let signal = |s| Some(Popup::Confirm(ProcessAction::Signal(pid, s)));
+
+            match key.code {
+                KeyCode::Char('t') => return signal(ProcessSignal::Terminate),
+                KeyCode::Char('K') => return signal(ProcessSignal::Kill),
+                KeyCode::Char('z') => return signal(ProcessSignal::Stop),
+                KeyCode::Char('c') => return signal(ProcessSignal::Continue),
+                KeyCode::Char(' ') if tree_view => {
+                    match self.collapsed.contains(&pid) {
+                        true => self.collapsed.remove(&pid),
+                        false => self.collapsed.insert(pid),
+                    };
+                }
+                KeyCode::Left if tree_view => {
+                    self.collapsed.insert(pid);
+                }
+                KeyCode::Right if tree_view => {
+                    self.collapsed.remove(&pid);
+                }
+                KeyCode::Char('n') => {
+                    return Some(match actions::get_nice(pid) {
+                        Ok(nice) => Popup::Renice { pid, name, nice },
+                        Err(e) => Popup::Error(format!("Could not read nice value: {}", e)),
+                    });
+                }
+                KeyCode::Char('a') => {
+                    return Some(match actions::get_affinity(pid) {
+                        Ok(current) => Popup::Affinity {
+                            pid,
+                            name,
+                            cpus: (0..actions::cpu_count())
+                                .map(|c| current.contains(&c))
+                                .collect(),
+                            cursor: 0,
+                        },
+                        Err(e) => Popup::Error(format!("Could not read cpu affinity: {}", e)),
+                    });
+                }
+                _ => (),
+            }
+        }
+
+        match key.code {
+            KeyCode::Up | KeyCode::Char('k') => self.selected = self.selected.saturating_sub(1),
+            KeyCode::Down | KeyCode::Char('j') => self.selected = self.selected.saturating_add(1),
+            KeyCode::PageUp => self.selected = self.selected.saturating_sub(20),
+            KeyCode::PageDown => self.selected = self.selected.saturating_add(20),
+            KeyCode::Home => self.selected = 0,
+            KeyCode::End => self.selected = usize::MAX,
+            KeyCode::Char('>') => self.sort_column = self.sort_column.next(),
+            KeyCode::Char('<') => self.sort_column = self.sort_column.prev(),
+            KeyCode::Char('r') => self.sort_descending = !self.sort_descending,
+            KeyCode::Char('v') => {
+                self.view = match self.view {
+                    ProcessView::Tree => ProcessView::List,
+                    _ => ProcessView::Tree,
+                }
+            }
+            KeyCode::Char('g') => {
+                self.view = match self.view {
+                    ProcessView::Grouped => ProcessView::List,
+                    _ => ProcessView::Grouped,
+                }
+            }
+            _ => (),
+        }
+
+        None
+    }
+}
+
+/// Contains the ui state of the home tab, which must persist between draw
+/// calls.
+pub struct HomeTabState {
+    /// Whether loopback, and virtual network interfaces are shown.
+    pub(crate) show_all_interfaces: bool,
+    /// Whether the cpu block shows frequencies instead of usage.
+    pub(crate) show_cpu_frequency: bool,
+    /// Rates used to estimate the cost, and emissions of the energy used.
+    pub(crate) energy_rates: EnergyRates,
+    /// Energy reading at which the user last reset the session. Energy is
+    /// shown relative to it.
+    pub(crate) energy_baseline: SessionEnergy,
+    /// Whether the user asked to reset the session. The app loop applies
+    /// this using the latest energy reading, right after handling the key.
+    pub(crate) reset_energy: bool,
+}
+
+impl HomeTabState {
+    fn new() -> Self {
+        HomeTabState {
+            show_all_interfaces: false,
+            show_cpu_frequency: false,
+            energy_rates: EnergyRates::default(),
+            energy_baseline: SessionEnergy::default(),
+            reset_energy: false,
+        }
+    }
+
+    /// Handle a key event directed at the home tab.
+    fn handle_key(&mut self, key: KeyEvent) {
+        match key.code {
+            KeyCode::Char('i') => self.show_all_interfaces = !self.show_all_interfaces,
+            KeyCode::Char('f') => self.show_cpu_frequency = !self.show_cpu_frequency,
+            KeyCode::Char('e') => self.reset_energy = true,
+            _ => (),
+        }
+    }
+}
+
+/// Contains the ui state of the gpu tab, which must persist between draw
+/// calls.
+pub struct GpuTabState {
+    /// Index of the selected row, counting the process tables of all gpus
+    /// as one list.
+    pub(crate) selected: usize,
+    /// Pid of the process in the selected row, as of the last draw, if that
+    /// process is also listed in the process table.
+    pub(crate) selected_pid: Option<u32>,
+    /// Whether the health details of each gpu are shown.
+    pub(crate) show_health: bool,
+}
+
+impl GpuTabState {
+    pub(crate) fn new() -> Self {
+        GpuTabState {
+            selected: 0,
+            selected_pid: None,
+            show_health: false,
+        }
+    }
+
+    /// Handle a key event directed at the gpu tab.
+    ///
+    /// Returns the pid of a process to show in the process table, if the
+    /// user has requested it.
+    fn handle_key(&mut self, key: KeyEvent) -> Option<u32> {
+        match key.code {
+            KeyCode::Up | KeyCode::Char('k') => self.selected = self.selected.saturating_sub(1),
+            KeyCode::Down | KeyCode::Char('j') => self.selected = self.selected.saturating_add(1),
+            KeyCode::Enter => return self.selected_pid,
+            KeyCode::Char('h') => self.show_health = !self.show_health,
+            _ => (),
+        }
+
+        None
+    }
+}
+
+/// Contains the ui state of the disks tab, which must persist between draw
+/// calls.
+pub struct DisksTabState {
+    /// Index of the first disk shown.
+    pub(crate) offset: usize,
+}
+
+impl DisksTabState {
+    fn new() -> Self {
+        DisksTabState { offset: 0 }
+    }
+
+    /// Handle a key event directed at the disks tab.
+    fn handle_key(&mut self, key: KeyEvent) {
+        match key.code {
+            KeyCode::Up | KeyCode::Char('k') => self.offset = self.offset.saturating_sub(1),
+            KeyCode::Down | KeyCode::Char('j') => self.offset = self.offset.saturating_add(1),
+            _ => (),
+        }
+    }
+}
+
+/// Contains the ui state of the sensors tab, which must persist between draw
+/// calls.
+pub struct SensorsTabState {
+    /// Index of the first line shown.
+    pub(crate) offset: usize,
+}
+
+impl SensorsTabState {
+    fn new() -> Self {
+        SensorsTabState { offset: 0 }
+    }
+
+    /// Handle a key event directed at the sensors tab.
+    fn handle_key(&mut self, key: KeyEvent) {
+        match key.code {
+            KeyCode::Up | KeyCode::Char('k') => self.offset = self.offset.saturating_sub(1),
+            KeyCode::Down | KeyCode::Char('j') => self.offset = self.offset.saturating_add(1),
+            KeyCode::PageUp => self.offset = self.offset.saturating_sub(20),
+            KeyCode::PageDown => self.offset = self.offset.saturating_add(20),
+            _ => (),
+        }
+    }
+}
+
+/// Contains the ui state of the cgroups tab, which must persist between draw
+/// calls.
+pub struct CgroupsTabState {
+    /// Index of the selected row, among the rows which are not folded away.
+    pub(crate) selected: usize,
+    pub(crate) offset: usize,
+    /// Paths of cgroups whose children are hidden.
+    pub(crate) collapsed: HashSet<String>,
+    /// Path of the cgroup in the selected row, as of the last draw. Its
+    /// pressure is polled, and shown below the tree.
+    pub(crate) selected_path: Option<String>,
+}
+
+impl CgroupsTabState {
+    fn new() -> Self {
+        CgroupsTabState {
+            selected: 0,
+            offset: 0,
+            collapsed: HashSet::new(),
+            selected_path: None,
+        }
+    }
+
+    /// Handle a key event directed at the cgroups tab.
+    fn handle_key(&mut self, key: KeyEvent) {
+        if let Some(path) = self.selected_path.clone() {
+            match key.code {
+                KeyCode::Char(' ') => {
+                    match self.collapsed.contains(&path) {
+                        true => self.collapsed.remove(&path),
+                        false => self.collapsed.insert(path),
+                    };
+                }
+                KeyCode::Left => {
+                    self.collapsed.insert(path);
+                }
+                KeyCode::Right => {
+                    self.collapsed.remove(&path);
+                }
+                _ => (),
+            }
+        }
+
+        match key.code {
+            KeyCode::Up | KeyCode::Char('k') => self.selected = self.selected.saturating_sub(1),
+            KeyCode::Down | KeyCode::Char('j') => self.selected = self.selected.saturating_add(1),
+            KeyCode::PageUp => self.selected = self.selected.saturating_sub(20),
+            KeyCode::PageDown => self.selected = self.selected.saturating_add(20),
+            KeyCode::Home => self.selected = 0,
+            KeyCode::End => self.selected = usize::MAX,
+            _ => (),
+        }
+    }
+}
+
 /// Contains the current ui state of the application.
 ///
 /// To create a shareable reference to an instance of this struct, use
 /// `new_shared()`, which will create an arcmutex around a new struct instance.
 pub struct UIState {
-    pub(crate) _current_tab: usize,
+    pub(crate) current_tab: Tab,
+    pub(crate) home_tab: HomeTabState,
+    pub(crate) process_table: ProcessTableState,
+    pub(crate) gpu_tab: GpuTabState,
+    pub(crate) disks_tab: DisksTabState,
+    pub(crate) sensors_tab: SensorsTabState,
+    pub(crate) cgroups_tab: CgroupsTabState,
+    pub(crate) popup: Option<Popup>,
 }
 
 impl UIState {
     /// Instantiate a new instance of this struct with default values.
     pub(crate) fn new() -> Self {
-        UIState { _current_tab: 0 }
+        UIState {
+            current_tab: Tab::Home,
+            home_tab: HomeTabState::new(),
+            process_table: ProcessTableState::new(),
+            gpu_tab: GpuTabState::new(),
+            disks_tab: DisksTabState::new(),
+            sensors_tab: SensorsTabState::new(),
+            cgroups_tab: CgroupsTabState::new(),
+            popup: None,
+        }
     }
 
     /// Instantiate a new instance of this struct, and wrap it in an
@@ -19,4 +507,58 @@ impl UIState {
     pub(crate) fn new_shared() -> Arc<Mutex<UIState>> {
         Arc::new(Mutex::new(Self::new()))
     }
+
+    /// Update the ui state in response to a key event.
+    ///
+    /// If a popup is open, it receives the key. Otherwise tab selection keys
+    /// are handled here, and all other keys are forwarded to the state of the
+    /// currently selected tab.
+    ///
+    /// Returns a [`ProcessAction`] if the user has confirmed one. It is up to
+    /// the caller to execute it, and report any error with
+    /// [`Self::show_error()`].
+    pub(crate) fn handle_key(&mut self, key: KeyEvent) -> Option<ProcessAction> {
+        if let Some(popup) = self.popup.take() {
+            let (popup, action) = popup.handle_key(key);
+            self.popup = popup;
+
+            return action;
+        }
+
+        let tab_count = Tab::ALL.len();
+
+        match key.code {
+            KeyCode::Tab => {
+                self.current_tab = Tab::ALL[(self.current_tab.index() + 1) % tab_count];
+            }
+            KeyCode::BackTab => {
+                self.current_tab = Tab::ALL[(self.current_tab.index() + tab_count - 1) % tab_count];
+            }
+            KeyCode::Char(c) if c.is_ascii_digit() && c != '0' => {
+                if let Some(tab) = Tab::ALL.get(c as usize - '1' as usize) {
+                    self.current_tab = *tab;
+                }
+            }
+            _ => match self.current_tab {
+                Tab::Processes => self.popup = self.process_table.handle_key(key),
+                Tab::Gpu => {
+                    if let Some(pid) = self.gpu_tab.handle_key(key) {
+                        self.process_table.focus_pid = Some(pid);
+                        self.current_tab = Tab::Processes;
+                    }
+                }
+                Tab::Home => self.home_tab.handle_key(key),
+                Tab::Disks => self.disks_tab.handle_key(key),
+                Tab::Sensors => self.sensors_tab.handle_key(key),
+                Tab::Cgroups => self.cgroups_tab.handle_key(key),
+            },
+        }
+
+        None
+    }
+
+    /// Open a popup displaying the given error message.
+    pub(crate) fn show_error(&mut self, message: String) {
+        self.popup = Some(Popup::Error(message));
+    }
 }
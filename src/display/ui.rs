@@ -17,21 +17,30 @@ use ratatui::{
     },
     style::{Color, Style, Stylize},
     text::{Line, Span},
-    widgets::{Block, Borders, Paragraph},
+    widgets::{Block, Borders, Paragraph, Tabs},
     Frame, Terminal,
 };
 
-use crate::{
-    monitoring::{polling::SystemPollResult, system::SystemData},
-    ringbuffer::RingBuffer,
-};
+use crate::monitoring::{history::PollHistory, system::SystemData};
 
 use super::{
-    cpu::{draw_cpu_average_block, draw_cpu_temp_block, draw_cpu_usage_block},
-    gpu::draw_gpu_info_block,
-    memory::draw_memory_usage_block,
-    state::UIState,
-    util::draw_sys_info,
+    battery::{battery_block_height, draw_battery_block},
+    cgroups::draw_cgroups_tab,
+    cpu::{
+        draw_cpu_average_block, draw_cpu_frequency_block, draw_cpu_power_block,
+        draw_cpu_temp_block, draw_cpu_usage_block,
+    },
+    disk::{disk_io_block_height, draw_disk_io_block, draw_disks_tab},
+    energy::{draw_energy_block, energy_block_height},
+    gpu::{draw_gpu_info_block, draw_gpu_tab},
+    memory::{draw_memory_usage_block, memory_block_height},
+    network::{draw_network_block, network_block_height},
+    popup::draw_popup,
+    pressure::{draw_pressure_block, pressure_block_height},
+    process::draw_process_table,
+    sensors::draw_sensors_tab,
+    state::{Tab, UIState},
+    util::{draw_activity_block, draw_sys_info, ACTIVITY_HEIGHT},
 };
 
 ///
@@ -49,12 +58,7 @@ pub fn shutdown_ui() -> Result<(), Box<dyn Error>> {
     Ok(())
 }
 
-pub fn draw(
-    state: &mut UIState,
-    data: &SystemData,
-    poll_data: &RingBuffer<SystemPollResult>,
-    f: &mut Frame,
-) {
+pub fn draw(state: &mut UIState, data: &SystemData, poll_data: &PollHistory, f: &mut Frame) {
     let l = Layout::default()
         .constraints(vec![Constraint::Length(2), Constraint::Percentage(99)])
         .split(f.size());
@@ -63,6 +67,40 @@ pub fn draw(
 
     draw_header(state, f, header_area);
 
+    let p = poll_data.latest();
+
+    match state.current_tab {
+        Tab::Home => draw_home_tab(state, data, poll_data, f, area),
+        Tab::Processes => draw_process_table(&p.processes, &mut state.process_table, f, area),
+        Tab::Gpu => draw_gpu_tab(poll_data, &mut state.gpu_tab, f, area),
+        Tab::Disks => draw_disks_tab(&data.disks, &mut state.disks_tab, f, area),
+        Tab::Sensors => draw_sensors_tab(&p.sensors, &mut state.sensors_tab, f, area),
+        Tab::Cgroups => draw_cgroups_tab(
+            &p.cgroup_tree,
+            &p.cgroup_pressure,
+            &mut state.cgroups_tab,
+            f,
+            area,
+        ),
+    }
+
+    if let Some(popup) = &state.popup {
+        draw_popup(popup, f, area);
+    }
+}
+
+/// Draws the home tab, which contains an overview of system information, and
+/// system activity, resource pressure, and cpu, memory, battery, gpu, disk
+/// I/O, and network usage.
+fn draw_home_tab(
+    state: &UIState,
+    data: &SystemData,
+    poll_data: &PollHistory,
+    f: &mut Frame,
+    area: Rect,
+) {
+    let p = poll_data.latest();
+
     let content_layout = Layout::default()
         .direction(Direction::Horizontal)
         .constraints(vec![Constraint::Length(45), Constraint::Percentage(99)])
@@ -72,54 +110,129 @@ pub fn draw(
 
     let sys_information_layout = Layout::default()
         .direction(Direction::Vertical)
-        .constraints(vec![Constraint::Length(10), Constraint::Percentage(99)])
+        .constraints(vec![
+            Constraint::Length(10),
+            Constraint::Length(ACTIVITY_HEIGHT),
+            Constraint::Percentage(99),
+        ])
         .split(layout_l);
 
-    let (sysinfo_layout, left_area) = (sys_information_layout[0], sys_information_layout[1]);
+    let (sysinfo_layout, activity_area, left_area) = (
+        sys_information_layout[0],
+        sys_information_layout[1],
+        sys_information_layout[2],
+    );
+
+    // The energy block is hidden on machines which report no power at all.
+    let energy = p.energy.since(&state.home_tab.energy_baseline);
+    let power = p.estimated_machine_power();
+    let show_energy = power.is_some() || p.energy.energy_wh > 0f64;
+    let energy_height = match show_energy {
+        true => energy_block_height(&state.home_tab.energy_rates),
+        false => 0,
+    };
 
     // Split left layout
     let left_layout = Layout::default()
         .direction(Direction::Vertical)
-        .constraints(vec![Constraint::Length(5), Constraint::Percentage(99)])
+        .constraints(vec![
+            Constraint::Length(memory_block_height(&p.memory, p.cgroup.as_ref())),
+            Constraint::Length(battery_block_height(&p.battery)),
+            Constraint::Length(energy_height),
+            Constraint::Percentage(99),
+        ])
         .split(left_area);
 
-    let (memory_area, gpu_area) = (left_layout[0], left_layout[1]);
+    let (memory_area, battery_area, energy_area, gpu_area) = (
+        left_layout[0],
+        left_layout[1],
+        left_layout[2],
+        left_layout[3],
+    );
 
     draw_sys_info(&data.info, f, sysinfo_layout);
+    draw_activity_block(&p.activity, f, activity_area);
 
-    // Draw right side
-    let p = poll_data.last().expect("No poll data could be read.");
+    // Split right side. The pressure, disk I/O and network blocks take what
+    // they need, but leave at least half of the column to the cpu blocks.
+    let show_all_interfaces = state.home_tab.show_all_interfaces;
+    let pressure_height = pressure_block_height(&p.pressure).min(layout_r.height / 4);
+    let disk_io_height = disk_io_block_height(&p.disk_io).min(layout_r.height / 4);
+    let network_height = network_block_height(&p.network, show_all_interfaces).min(
+        (layout_r.height / 2)
+            .saturating_sub(pressure_height)
+            .saturating_sub(disk_io_height),
+    );
 
-    // Split right side
     let right_layout = Layout::default()
         .direction(Direction::Vertical)
         .constraints(vec![
             Constraint::Length(3),
             Constraint::Length(3),
-            Constraint::Percentage(99),
+            Constraint::Min(0),
+            Constraint::Length(pressure_height),
+            Constraint::Length(disk_io_height),
+            Constraint::Length(network_height),
         ])
         .split(layout_r);
 
     let (cpu_temp_area, cpu_average_area, cpu_usage_area) =
         (right_layout[0], right_layout[1], right_layout[2]);
+    let (pressure_area, disk_io_area, network_area) =
+        (right_layout[3], right_layout[4], right_layout[5]);
+
+    // Cpu power sits next to the temperature, if the cpu reports it.
+    match p.cpu_power.is_empty() {
+        true => draw_cpu_temp_block(p.cpu_temperature.as_ref(), f, cpu_temp_area),
+        false => {
+            let l = Layout::default()
+                .direction(Direction::Horizontal)
+                .constraints(vec![Constraint::Percentage(40), Constraint::Percentage(60)])
+                .split(cpu_temp_area);
 
-    draw_cpu_temp_block(&p.cpu_temperature, f, cpu_temp_area);
+            draw_cpu_temp_block(p.cpu_temperature.as_ref(), f, l[0]);
+            draw_cpu_power_block(p, f, l[1]);
+        }
+    }
     draw_cpu_average_block(&p.cpu_usage, f, cpu_average_area);
-    draw_cpu_usage_block(&p.cpu_usage, f, cpu_usage_area);
-    draw_memory_usage_block(
-        data.info.total_memory as f32,
-        p.memory_usage.value,
+
+    match state.home_tab.show_cpu_frequency {
+        true => draw_cpu_frequency_block(&p.cpu_frequency, f, cpu_usage_area),
+        false => draw_cpu_usage_block(
+            &p.cpu_usage,
+            &p.cpu_times,
+            p.cgroup.as_ref(),
+            f,
+            cpu_usage_area,
+        ),
+    }
+
+    draw_memory_usage_block(&p.memory, p.cgroup.as_ref(), f, memory_area);
+
+    if !p.battery.batteries.is_empty() {
+        draw_battery_block(&p.battery, f, battery_area);
+    }
+
+    if show_energy {
+        draw_energy_block(&energy, power, &state.home_tab.energy_rates, f, energy_area);
+    }
+
+    draw_gpu_info_block(&p.gpu_info, &p.gpu_errors, f, gpu_area);
+    draw_pressure_block(
+        &p.pressure,
+        " Pressure (10s / 60s / 300s) ",
         f,
-        memory_area,
+        pressure_area,
     );
-    draw_gpu_info_block(&p.gpu_info, f, gpu_area);
+    draw_disk_io_block(&p.disk_io, f, disk_io_area);
+    draw_network_block(poll_data, show_all_interfaces, f, network_area);
 }
 
 /// Draws the header which sits at the top of the ui.
 ///
 /// The header contains a title, version information, and tab information.
 /// The header also contains current keybinds.
-fn draw_header(_state: &UIState, f: &mut Frame, area: Rect) {
+fn draw_header(state: &UIState, f: &mut Frame, area: Rect) {
     // Draw header bg and outer styling elements
     let header_block = Block::default().borders(Borders::BOTTOM);
 
@@ -130,10 +243,14 @@ fn draw_header(_state: &UIState, f: &mut Frame, area: Rect) {
     // Split layout
     let l = Layout::default()
         .direction(Horizontal)
-        .constraints(vec![Constraint::Percentage(50), Constraint::Length(5)])
+        .constraints(vec![
+            Constraint::Length(20),
+            Constraint::Min(0),
+            Constraint::Length(28),
+        ])
         .split(header_area);
 
-    let (title_area, hints_area) = (l[0], l[1]);
+    let (title_area, tabs_area, hints_area) = (l[0], l[1], l[2]);
 
     let title = Paragraph::new(vec![Line::from(vec![
         Span::styled("MAINFRAME", Style::new().bold()),
@@ -145,13 +262,25 @@ fn draw_header(_state: &UIState, f: &mut Frame, area: Rect) {
     ])])
     .alignment(ratatui::layout::Alignment::Left);
 
-    // let tabs = Tabs::new(vec!["Home", "Usage", "Disks", "Help"])
-    //     .select(state.current_tab)
-    //     .padding(" ", " ");
+    let tabs = Tabs::new(
+        Tab::ALL
+            .iter()
+            .enumerate()
+            .map(|(i, t)| format!("{} {}", i + 1, t.title()))
+            .collect(),
+    )
+    .select(state.current_tab.index())
+    .highlight_style(Style::new().bold().reversed())
+    .padding(" ", " ");
 
-    let keybind_hints = Paragraph::new(Line::styled(" q: Quit ", Style::new().bg(Color::DarkGray)))
-        .alignment(ratatui::layout::Alignment::Right);
+    let keybind_hints = Paragraph::new(Line::from(vec![
+        Span::styled(" Tab: Switch tab ", Style::new().bg(Color::DarkGray)),
+        Span::raw(" "),
+        Span::styled(" q: Quit ", Style::new().bg(Color::DarkGray)),
+    ]))
+    .alignment(ratatui::layout::Alignment::Right);
 
     f.render_widget(title, title_area);
+    f.render_widget(tabs, tabs_area);
     f.render_widget(keybind_hints, hints_area);
 }
@@ -4,11 +4,14 @@ use ratatui::{
     layout::{Constraint, Direction, Layout, Rect},
     style::{Color, Modifier, Style},
     text::{Line, Span},
-    widgets::{Block, BorderType, Borders, Padding, Paragraph},
+    widgets::{Block, BorderType, Borders, Gauge, Padding, Paragraph},
     Frame,
 };
 
-use crate::monitoring::system::{DiskInformation, SystemInformation};
+use crate::monitoring::{
+    procstat::SystemActivity,
+    system::{DiskInformation, SystemInformation},
+};
 
 pub fn draw_sys_info(s: &SystemInformation, f: &mut Frame, area: Rect) {
     let style_category = |s: String| Span::styled(s, Style::new().add_modifier(Modifier::BOLD));
@@ -52,12 +55,95 @@ pub fn draw_sys_info(s: &SystemInformation, f: &mut Frame, area: Rect) {
     f.render_widget(p, area)
 }
 
+/// Format a duration in seconds as days, hours, and minutes.
+pub fn format_uptime(seconds: u64) -> String {
+    let (days, hours, minutes) = (seconds / 86400, seconds / 3600 % 24, seconds / 60 % 60);
+
+    match days {
+        0 => format!("{}h {}m", hours, minutes),
+        _ => format!("{}d {}h {}m", days, hours, minutes),
+    }
+}
+
+/// Rows taken up by [`draw_activity_block`].
+pub const ACTIVITY_HEIGHT: u16 = 6;
+
+/// Draws a compact block with load averages, uptime, runnable and blocked
+/// tasks, and context switch, fork, and interrupt rates. It fits the width of
+/// the system information block.
+pub fn draw_activity_block(a: &SystemActivity, f: &mut Frame, area: Rect) {
+    let style_category =
+        |s: &'static str| Span::styled(s, Style::new().add_modifier(Modifier::BOLD));
+    let style_value = |s: String| Span::styled(s, Style::new());
+
+    let block = Block::new()
+        .title(" Activity ")
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded)
+        .padding(Padding::new(1, 1, 0, 0));
+
+    let inner_area = block.inner(area);
+
+    f.render_widget(block, area);
+
+    let l = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints(vec![Constraint::Length(26), Constraint::Min(0)])
+        .split(inner_area);
+
+    let [load_1, load_5, load_15] = a.load_average;
+
+    let left = vec![
+        Line::from(vec![
+            style_category("Load:    "),
+            style_value(format!("{:.2} {:.2} {:.2}", load_1, load_5, load_15)),
+        ]),
+        Line::from(vec![
+            style_category("Uptime:  "),
+            style_value(format_uptime(a.uptime_seconds)),
+        ]),
+        Line::from(vec![
+            style_category("Running: "),
+            style_value(a.procs_running.to_string()),
+        ]),
+        Line::from(vec![
+            style_category("Blocked: "),
+            style_value(a.procs_blocked.to_string()),
+        ]),
+    ];
+
+    let right = vec![
+        Line::from(vec![
+            style_category("Ctx/s:   "),
+            style_value(format!("{:.0}", a.context_switches_per_sec)),
+        ]),
+        Line::from(vec![
+            style_category("Forks/s: "),
+            style_value(format!("{:.0}", a.forks_per_sec)),
+        ]),
+        Line::from(vec![
+            style_category("Intr/s:  "),
+            style_value(format!("{:.0}", a.interrupts_per_sec)),
+        ]),
+    ];
+
+    f.render_widget(Paragraph::new(left), l[0]);
+    f.render_widget(Paragraph::new(right), l[1]);
+}
+
+/// Rows taken up by [`draw_disk_info`].
+pub const DISK_INFO_HEIGHT: u16 = 7;
+
+/// Draws the mount point, filesystem, space and inode usage of a disk.
 pub fn draw_disk_info(d: &DiskInformation, f: &mut Frame, area: Rect) {
     // Surround display information in block
     let block = Block::default()
         .borders(Borders::TOP)
         .padding(Padding::new(2, 2, 1, 1))
-        .title(format!(" Disk {} ", d.name));
+        .title(Span::styled(
+            format!(" {} ", d.mount_point),
+            Style::new().add_modifier(Modifier::BOLD),
+        ));
 
     let inner_area = block.inner(area);
 
@@ -67,60 +153,103 @@ pub fn draw_disk_info(d: &DiskInformation, f: &mut Frame, area: Rect) {
     let layout = Layout::default()
         .direction(Direction::Vertical)
         .constraints(vec![
-            Constraint::Length(4),
+            Constraint::Length(3),
             Constraint::Length(1),
-            Constraint::Percentage(99),
+            Constraint::Min(0),
         ])
         .split(inner_area);
 
-    // Draw name and title information
-    let formatted_message = vec![
-        format!("{} {}", d.kind, d.name),
-        format!(
-            "Free space: {} ({}b)",
-            human_bytes(d.available_space as f64),
-            d.available_space
+    // Draw name and usage information
+    let mut header = vec![Span::raw(format!(
+        "{} {} ({})",
+        d.kind, d.name, d.file_system
+    ))];
+
+    if d.read_only {
+        header.push(Span::styled(" read-only", Style::new().fg(Color::Yellow)));
+    }
+
+    // Some filesystems, such as btrfs, do not have a fixed number of inodes.
+    let inodes = match d.inodes_total {
+        0 => "Inodes: n/a".to_string(),
+        total => format!(
+            "Inodes: {} used of {} ({:.1}%)",
+            d.inodes_used(),
+            total,
+            100.0 * d.inodes_used() as f64 / total as f64
         ),
-        format!(
-            "Total space: {} ({}b)",
+    };
+
+    let text = vec![
+        Line::from(header),
+        Line::from(format!(
+            "Used: {}  Free: {}  Total: {}",
+            human_bytes(d.used_space() as f64),
+            human_bytes(d.available_space as f64),
             human_bytes(d.total_space as f64),
-            d.total_space
-        ),
+        )),
+        Line::from(inodes),
     ];
 
-    f.render_widget(Paragraph::new(formatted_message.join("\n")), layout[0]);
-
-    // let usage_percent = 1.0 - (d.available_space as f64 / d.total_space as f64);
-
-    // let gauge = Gauge::default()
-    //     .gauge_style(
-    //         Style::default()
-    //             .fg(get_color_for_range(usage_percent, (0.0, 1.0)))
-    //             .bg(Color::DarkGray),
-    //     )
-    //     .label(format!(
-    //         "{} free ({}% used)",
-    //         human_bytes(d.available_space as f64),
-    //         format!("{:.2}", 100.0 * usage_percent)
-    //     ))
-    //     .ratio(usage_percent);
+    f.render_widget(Paragraph::new(text), layout[0]);
+
+    let usage_percent = match d.total_space {
+        0 => 0.0,
+        total => d.used_space() as f64 / total as f64,
+    };
+
+    let gauge = Gauge::default()
+        .gauge_style(
+            Style::default()
+                .fg(get_color_for_range(usage_percent as f32, (0.0, 1.0)))
+                .bg(Color::DarkGray),
+        )
+        .label(format!(
+            "{} free ({:.2}% used)",
+            human_bytes(d.available_space as f64),
+            100.0 * usage_percent
+        ))
+        .ratio(usage_percent);
 
-    // f.render_widget(gauge, layout[1]);
+    f.render_widget(gauge, layout[1]);
 }
 
+/// Get the color of a value, from green to red, by how far into the range
+/// `r` it is.
 pub fn get_color_for_range(v: f32, r: (f32, f32)) -> Color {
-    let x = (v - r.0) / r.1;
+    let x = (v - r.0) / (r.1 - r.0);
 
     match x {
-        x if (0.0..0.6).contains(&x) => Color::Green,
+        x if x < 0.6 => Color::Green,
         x if (0.6..0.85).contains(&x) => Color::Yellow,
         _ => Color::Red,
     }
 }
 
-pub fn default_block(title: &str) -> Block {
+pub fn default_block(title: &str) -> Block<'_> {
     Block::default()
         .title(title)
         .border_type(BorderType::Rounded)
         .borders(Borders::ALL)
 }
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+
+    #[test]
+    fn test_get_color_for_range() {
+        assert!(get_color_for_range(0.5, (0f32, 2f32)) == Color::Green);
+        assert!(get_color_for_range(1.5, (0f32, 2f32)) == Color::Yellow);
+        assert!(get_color_for_range(1.9, (0f32, 2f32)) == Color::Red);
+        assert!(get_color_for_range(3.5e9, (0f32, 4e9)) == Color::Red);
+        assert!(get_color_for_range(15.0, (10f32, 20f32)) == Color::Green);
+    }
+
+    #[test]
+    fn test_format_uptime() {
+        assert!(format_uptime(59) == "0h 0m");
+        assert!(format_uptime(3 * 3600 + 25 * 60) == "3h 25m");
+        assert!(format_uptime(2 * 86400 + 3600 + 60) == "2d 1h 1m");
+    }
+}
@@ -6,6 +6,7 @@ use clap::Parser;
 
 use mainframe::app::MainFrameApp;
 use mainframe::cli;
+use mainframe::monitoring::energy::EnergyRates;
 use mainframe::panic_handler;
 
 #[tokio::main]
@@ -16,7 +17,11 @@ async fn main() -> Result<(), Box<dyn Error>> {
 
     let app = MainFrameApp::new()
         .with_poll_rate(opts.poll_rate)
-        .with_refresh_rate(opts.refresh_rate);
+        .with_refresh_rate(opts.refresh_rate)
+        .with_energy_rates(EnergyRates {
+            price_per_kwh: opts.price_per_kwh,
+            co2_grams_per_kwh: opts.co2_per_kwh,
+        });
 
     app.run().await.unwrap();
 
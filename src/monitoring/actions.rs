@@ -0,0 +1,221 @@
+/// Module actions contains functionality to act on running processes, such as
+/// sending signals, or changing their scheduling priority.
+use std::io;
+
+/// [`ProcessSignal`] enumerates the signals which can be sent to a process.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProcessSignal {
+    Terminate,
+    Kill,
+    Stop,
+    Continue,
+}
+
+impl ProcessSignal {
+    /// Conventional name of the signal, eg. `SIGTERM`.
+    pub fn name(&self) -> &'static str {
+        match self {
+            ProcessSignal::Terminate => "SIGTERM",
+            ProcessSignal::Kill => "SIGKILL",
+            ProcessSignal::Stop => "SIGSTOP",
+            ProcessSignal::Continue => "SIGCONT",
+        }
+    }
+
+    fn as_raw(&self) -> libc::c_int {
+        match self {
+            ProcessSignal::Terminate => libc::SIGTERM,
+            ProcessSignal::Kill => libc::SIGKILL,
+            ProcessSignal::Stop => libc::SIGSTOP,
+            ProcessSignal::Continue => libc::SIGCONT,
+        }
+    }
+}
+
+/// [`ProcessAction`] describes an action to take on a single process.
+///
+/// Actions are only described by this enum, and are not carried out until
+/// [`ProcessAction::execute()`] is called.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ProcessAction {
+    /// Send the given signal to the process.
+    Signal(u32, ProcessSignal),
+    /// Set the nice value of the process.
+    Renice(u32, i32),
+    /// Restrict the process to run on the given logical cpus only.
+    SetAffinity(u32, Vec<usize>),
+}
+
+impl ProcessAction {
+    /// Short human readable description of this action.
+    pub fn describe(&self) -> String {
+        match self {
+            ProcessAction::Signal(pid, signal) => format!("Send {} to {}", signal.name(), pid),
+            ProcessAction::Renice(pid, nice) => format!("Set nice value of {} to {}", pid, nice),
+            ProcessAction::SetAffinity(pid, cpus) => format!(
+                "Pin {} to cpus {}",
+                pid,
+                cpus.iter()
+                    .map(|c| c.to_string())
+                    .collect::<Vec<_>>()
+                    .join(",")
+            ),
+        }
+    }
+
+    /// Carry out this action.
+    ///
+    /// Failures, such as insufficient permissions or a process which has
+    /// since exited, are returned as the underlying os error.
+    pub fn execute(&self) -> io::Result<()> {
+        match self {
+            ProcessAction::Signal(pid, signal) => send_signal(*pid, *signal),
+            ProcessAction::Renice(pid, nice) => set_nice(*pid, *nice),
+            ProcessAction::SetAffinity(pid, cpus) => set_affinity(*pid, cpus),
+        }
+    }
+}
+
+fn check(ret: libc::c_int) -> io::Result<()> {
+    match ret {
+        -1 => Err(io::Error::last_os_error()),
+        _ => Ok(()),
+    }
+}
+
+fn send_signal(pid: u32, signal: ProcessSignal) -> io::Result<()> {
+    // SAFETY: kill has no memory safety requirements.
+    check(unsafe { libc::kill(pid as libc::pid_t, signal.as_raw()) })
+}
+
+fn set_nice(pid: u32, nice: i32) -> io::Result<()> {
+    // SAFETY: setpriority has no memory safety requirements.
+    check(unsafe { libc::setpriority(libc::PRIO_PROCESS, pid as libc::id_t, nice) })
+}
+
+/// Get the current nice value of the process `pid`.
+#[cfg(target_os = "linux")]
+pub fn get_nice(pid: u32) -> io::Result<i32> {
+    // getpriority can legitimately return -1, so errno has to be cleared
+    // beforehand in order to detect failure.
+    // SAFETY: __errno_location always returns a valid thread local pointer.
+    unsafe { *libc::__errno_location() = 0 };
+
+    // SAFETY: getpriority has no memory safety requirements.
+    let nice = unsafe { libc::getpriority(libc::PRIO_PROCESS, pid as libc::id_t) };
+
+    match io::Error::last_os_error() {
+        e if nice == -1 && e.raw_os_error() != Some(0) => Err(e),
+        _ => Ok(nice),
+    }
+}
+
+/// Get the current nice value of the process `pid`.
+#[cfg(not(target_os = "linux"))]
+pub fn get_nice(_pid: u32) -> io::Result<i32> {
+    Err(io::ErrorKind::Unsupported.into())
+}
+
+/// Get the number of logical cpus configured on this system.
+pub fn cpu_count() -> usize {
+    // SAFETY: sysconf has no memory safety requirements.
+    match unsafe { libc::sysconf(libc::_SC_NPROCESSORS_CONF) } {
+        n if n > 0 => n as usize,
+        _ => 1,
+    }
+}
+
+/// Get the logical cpus the process `pid` is allowed to run on.
+#[cfg(target_os = "linux")]
+pub fn get_affinity(pid: u32) -> io::Result<Vec<usize>> {
+    // SAFETY: cpu_set_t is a plain bitmask, for which all zeros is valid.
+    let mut set: libc::cpu_set_t = unsafe { std::mem::zeroed() };
+
+    // SAFETY: set is a valid cpu_set_t of the given size.
+    check(unsafe {
+        libc::sched_getaffinity(
+            pid as libc::pid_t,
+            std::mem::size_of::<libc::cpu_set_t>(),
+            &mut set,
+        )
+    })?;
+
+    Ok((0..libc::CPU_SETSIZE as usize)
+        // SAFETY: cpu index is within CPU_SETSIZE.
+        .filter(|cpu| unsafe { libc::CPU_ISSET(*cpu, &set) })
+        .collect())
+}
+
+/// Get the logical cpus the process `pid` is allowed to run on.
+#[cfg(not(target_os = "linux"))]
+pub fn get_affinity(_pid: u32) -> io::Result<Vec<usize>> {
+    Err(io::ErrorKind::Unsupported.into())
+}
+
+#[cfg(target_os = "linux")]
+fn set_affinity(pid: u32, cpus: &[usize]) -> io::Result<()> {
+    if cpus.is_empty() {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "at least one cpu must be selected",
+        ));
+    }
+
+    // SAFETY: cpu_set_t is a plain bitmask, for which all zeros is valid.
+    let mut set: libc::cpu_set_t = unsafe { std::mem::zeroed() };
+
+    for cpu in cpus.iter().filter(|c| **c < libc::CPU_SETSIZE as usize) {
+        // SAFETY: cpu index is within CPU_SETSIZE.
+        unsafe { libc::CPU_SET(*cpu, &mut set) };
+    }
+
+    // SAFETY: set is a valid cpu_set_t of the given size.
+    check(unsafe {
+        libc::sched_setaffinity(
+            pid as libc::pid_t,
+            std::mem::size_of::<libc::cpu_set_t>(),
+            &set,
+        )
+    })
+}
+
+#[cfg(not(target_os = "linux"))]
+fn set_affinity(_pid: u32, _cpus: &[usize]) -> io::Result<()> {
+    Err(io::ErrorKind::Unsupported.into())
+}
+
+#[cfg(test)]
+pub mod tests {
+    use std::process::Command;
+
+    use super::*;
+
+    #[test]
+    fn test_signal_child() {
+        let mut child = Command::new("sleep").arg("30").spawn().unwrap();
+
+        assert!(ProcessAction::Signal(child.id(), ProcessSignal::Terminate)
+            .execute()
+            .is_ok());
+        assert!(!child.wait().unwrap().success());
+    }
+
+    #[test]
+    fn test_renice_child() {
+        let mut child = Command::new("sleep").arg("30").spawn().unwrap();
+
+        assert!(ProcessAction::Renice(child.id(), 5).execute().is_ok());
+        assert!(get_nice(child.id()).unwrap() == 5);
+
+        child.kill().unwrap();
+        child.wait().unwrap();
+    }
+
+    #[test]
+    fn test_missing_process_is_error() {
+        // Pids are capped well below u32::MAX, so this process cannot exist.
+        let action = ProcessAction::Signal(i32::MAX as u32, ProcessSignal::Continue);
+
+        assert!(action.execute().unwrap_err().raw_os_error() == Some(libc::ESRCH));
+    }
+}
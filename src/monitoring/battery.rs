@@ -0,0 +1,229 @@
+/// Module battery contains functionality to poll batteries, and AC adapters
+/// through `/sys/class/power_supply`.
+use std::{fs, path::Path, path::PathBuf};
+
+use super::sysfs::{read_string, read_value};
+
+/// Enumerates the charging states reported by a battery.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BatteryStatus {
+    Charging,
+    Discharging,
+    Full,
+    /// Plugged in, but held below full charge, as with charge thresholds.
+    NotCharging,
+    Unknown,
+}
+
+impl BatteryStatus {
+    fn from_sysfs(status: &str) -> Self {
+        match status {
+            "Charging" => BatteryStatus::Charging,
+            "Discharging" => BatteryStatus::Discharging,
+            "Full" => BatteryStatus::Full,
+            "Not charging" => BatteryStatus::NotCharging,
+            _ => BatteryStatus::Unknown,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            BatteryStatus::Charging => "Charging",
+            BatteryStatus::Discharging => "Discharging",
+            BatteryStatus::Full => "Full",
+            BatteryStatus::NotCharging => "Not charging",
+            BatteryStatus::Unknown => "Unknown",
+        }
+    }
+}
+
+/// [`Battery`] contains a single reading of a system battery. Energies are
+/// in watt hours, and are `None` if the driver does not report them.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Battery {
+    /// Name of the power supply, such as `BAT0`.
+    pub name: String,
+    pub status: BatteryStatus,
+    /// Charge, as a percentage of the current full capacity.
+    pub capacity: f32,
+    /// Power flowing into, or out of the battery, in watts.
+    pub power: Option<f32>,
+    pub energy_now: Option<f32>,
+    pub energy_full: Option<f32>,
+    pub energy_full_design: Option<f32>,
+}
+
+impl Battery {
+    /// Full capacity, as a percentage of the capacity the battery was
+    /// designed with.
+    pub fn health(&self) -> Option<f32> {
+        let design = self.energy_full_design.filter(|e| *e > 0f32)?;
+
+        Some(100f32 * self.energy_full? / design)
+    }
+
+    /// Seconds until the battery is empty when discharging, or full when
+    /// charging, at the current power draw.
+    pub fn time_remaining(&self) -> Option<u64> {
+        let power = self.power.filter(|p| *p > 0f32)?;
+
+        let energy = match self.status {
+            BatteryStatus::Discharging => self.energy_now?,
+            BatteryStatus::Charging => (self.energy_full? - self.energy_now?).max(0f32),
+            _ => return None,
+        };
+
+        Some((3600f32 * energy / power) as u64)
+    }
+}
+
+/// [`BatteryPollResult`] contains every system battery, and the state of the
+/// AC adapter.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct BatteryPollResult {
+    pub batteries: Vec<Battery>,
+    /// Whether any AC adapter is plugged in, or `None` if there is none.
+    pub ac_online: Option<bool>,
+}
+
+/// Read a battery from its power supply directory.
+///
+/// Drivers report either energy (µWh) and power (µW), or charge (µAh) and
+/// current (µA). Charge, and current are converted using the battery's
+/// voltage.
+fn read_battery(path: &Path, name: String) -> Battery {
+    let micro = |file: &str| read_value::<f32>(path.join(file)).map(|v| v / 1_000_000f32);
+
+    let voltage = micro("voltage_min_design").or_else(|| micro("voltage_now"));
+    let energy = |kind: &str| {
+        micro(&format!("energy_{}", kind))
+            .or_else(|| Some(micro(&format!("charge_{}", kind))? * voltage?))
+    };
+
+    let power = micro("power_now")
+        .or_else(|| Some(micro("current_now")? * micro("voltage_now").or(voltage)?))
+        .map(f32::abs);
+
+    let energy_now = energy("now");
+    let energy_full = energy("full");
+
+    Battery {
+        name,
+        status: BatteryStatus::from_sysfs(&read_string(path.join("status")).unwrap_or_default()),
+        capacity: read_value(path.join("capacity"))
+            .or_else(|| Some(100f32 * energy_now? / energy_full?))
+            .unwrap_or(0f32),
+        power,
+        energy_now,
+        energy_full,
+        energy_full_design: energy("full_design"),
+    }
+}
+
+/// [`BatteryPoller`] reads every power supply found under
+/// `<sysfs root>/class/power_supply`.
+///
+/// Batteries of peripherals, such as wireless mice, are skipped.
+pub struct BatteryPoller {
+    sysfs_root: PathBuf,
+}
+
+impl Default for BatteryPoller {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl BatteryPoller {
+    /// Create a new poller reading from `/sys`.
+    pub fn new() -> Self {
+        Self::with_sysfs_root("/sys")
+    }
+
+    /// Create a new poller reading from the given sysfs root.
+    pub fn with_sysfs_root(root: impl Into<PathBuf>) -> Self {
+        BatteryPoller {
+            sysfs_root: root.into(),
+        }
+    }
+
+    /// Obtain a [`BatteryPollResult`] reading, with batteries ordered by
+    /// name.
+    pub fn poll(&self) -> BatteryPollResult {
+        let mut res = BatteryPollResult::default();
+
+        let Ok(entries) = fs::read_dir(self.sysfs_root.join("class/power_supply")) else {
+            return res;
+        };
+
+        let mut supplies: Vec<(String, PathBuf)> = entries
+            .flatten()
+            .map(|e| (e.file_name().to_string_lossy().to_string(), e.path()))
+            .collect();
+
+        supplies.sort();
+
+        for (name, path) in supplies {
+            match read_string(path.join("type")).as_deref() {
+                Some("Battery") => {
+                    if read_string(path.join("scope")).as_deref() == Some("Device")
+                        || read_value::<u8>(path.join("present")) == Some(0)
+                    {
+                        continue;
+                    }
+
+                    res.batteries.push(read_battery(&path, name));
+                }
+                Some("Mains") => {
+                    let online = read_value::<u8>(path.join("online")) == Some(1);
+
+                    res.ac_online = Some(res.ac_online.unwrap_or(false) || online);
+                }
+                _ => (),
+            }
+        }
+
+        res
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+
+    fn fixture_root() -> String {
+        format!("{}/tests/fixtures/sys", env!("CARGO_MANIFEST_DIR"))
+    }
+
+    #[test]
+    fn test_poll_fixture() {
+        let res = BatteryPoller::with_sysfs_root(fixture_root()).poll();
+
+        // hidpp_battery_0 belongs to a peripheral.
+        let names: Vec<&str> = res.batteries.iter().map(|b| b.name.as_str()).collect();
+        assert!(names == ["BAT0", "BAT1"]);
+        assert!(res.ac_online == Some(false));
+
+        let bat0 = &res.batteries[0];
+        assert!(bat0.status == BatteryStatus::Discharging);
+        assert!(bat0.capacity == 54.0);
+        assert!(bat0.power == Some(12.5));
+        assert!(bat0.time_remaining() == Some(7776));
+        assert!(bat0.health().is_some_and(|h| (h - 87.72).abs() < 0.01));
+
+        // BAT1 reports charge, and current rather than energy, and power.
+        let bat1 = &res.batteries[1];
+        assert!(bat1.status == BatteryStatus::Charging);
+        assert!(bat1.energy_now == Some(44.0));
+        assert!(bat1.power == Some(24.0));
+        assert!(bat1.time_remaining() == Some(1650));
+        assert!(bat1.health() == Some(100.0));
+    }
+
+    #[test]
+    fn test_poll_missing() {
+        let res = BatteryPoller::with_sysfs_root("/nonexistent").poll();
+
+        assert!(res.batteries.is_empty() && res.ac_online.is_none());
+    }
+}
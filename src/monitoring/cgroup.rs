@@ -0,0 +1,378 @@
+/// Module cgroup contains functionality to poll the resource limits of the
+/// cgroup mainframe runs in, such as the limits of a container, for both
+/// cgroup v1, and v2.
+use std::{
+    path::{Path, PathBuf},
+    time::Instant,
+};
+
+use super::sysfs::{read_string, read_value};
+
+/// Cgroup v1 memory limits at or above this value mean there is no limit.
+const V1_UNLIMITED: u64 = 1 << 62;
+
+/// Enumerates the versions of the cgroup hierarchy.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CgroupVersion {
+    V1,
+    V2,
+}
+
+impl CgroupVersion {
+    pub fn name(&self) -> &'static str {
+        match self {
+            CgroupVersion::V1 => "cgroup v1",
+            CgroupVersion::V2 => "cgroup v2",
+        }
+    }
+}
+
+/// [`CgroupLimits`] contains the resource limits of a cgroup, and its usage
+/// of the limited resources. Limits are `None` if they are not set.
+///
+/// Limits set on parent cgroups apply too, so the tightest limit along the
+/// path to the root is reported.
+#[derive(Clone, Debug, PartialEq)]
+pub struct CgroupLimits {
+    pub version: CgroupVersion,
+    /// Path of the cgroup, such as `/system.slice/docker-0123.scope`.
+    pub path: String,
+    /// Memory limit, in bytes.
+    pub memory_limit: Option<u64>,
+    /// Memory used by the cgroup, including page cache, in bytes.
+    pub memory_usage: Option<u64>,
+    /// Cpu time the cgroup may use, in cpus, from its cfs quota, and period.
+    pub cpu_quota: Option<f32>,
+    /// Cpus the cgroup may run on. Empty if it could not be read.
+    pub cpuset: Vec<usize>,
+    /// Cpu time used since the previous poll, in cpus. `None` on the first
+    /// poll.
+    pub cpu_usage: Option<f32>,
+}
+
+impl CgroupLimits {
+    /// The number of cpus the cgroup may use, if this is less than
+    /// `host_cpus`. Both the cpu quota, and the cpuset are taken into
+    /// account.
+    pub fn cpu_limit(&self, host_cpus: usize) -> Option<f32> {
+        let cpuset = Some(self.cpuset.len())
+            .filter(|n| *n > 0 && *n < host_cpus)
+            .map(|n| n as f32);
+
+        [self.cpu_quota, cpuset]
+            .into_iter()
+            .flatten()
+            .filter(|l| *l < host_cpus as f32)
+            .reduce(f32::min)
+    }
+
+    /// Whether the cpuset leaves out some of the `host_cpus`.
+    pub fn cpuset_limited(&self, host_cpus: usize) -> bool {
+        !self.cpuset.is_empty() && self.cpuset.len() < host_cpus
+    }
+}
+
+/// Parse a cpu list, such as `0-3,6`, into the cpus it contains.
+pub fn parse_cpu_list(list: &str) -> Vec<usize> {
+    list.split(',')
+        .filter_map(|range| {
+            let (start, end) = match range.trim().split_once('-') {
+                Some((start, end)) => (start.parse().ok()?, end.parse().ok()?),
+                None => {
+                    let cpu = range.trim().parse().ok()?;
+                    (cpu, cpu)
+                }
+            };
+
+            Some(start..=end)
+        })
+        .flatten()
+        .collect()
+}
+
+/// Format cpus as a cpu list, such as `0-3,6`.
+pub fn format_cpu_list(cpus: &[usize]) -> String {
+    let mut ranges: Vec<(usize, usize)> = vec![];
+
+    for cpu in cpus {
+        match ranges.last_mut() {
+            Some((_, end)) if *end + 1 == *cpu => *end = *cpu,
+            _ => ranges.push((*cpu, *cpu)),
+        }
+    }
+
+    ranges
+        .iter()
+        .map(|(start, end)| match start == end {
+            true => start.to_string(),
+            false => format!("{}-{}", start, end),
+        })
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+/// Find the cgroup v2 hierarchy below the cgroup root. This is the root
+/// itself on unified systems, or its `unified` directory on hybrid systems,
+/// which mount controllers as cgroup v1.
+pub fn unified_root(cgroup_root: &Path) -> Option<PathBuf> {
+    [cgroup_root.to_path_buf(), cgroup_root.join("unified")]
+        .into_iter()
+        .find(|root| root.join("cgroup.controllers").exists())
+}
+
+/// Find the path of the cgroup of a controller, in the contents of
+/// `/proc/<pid>/cgroup`. For cgroup v2, `controller` is empty.
+pub(crate) fn cgroup_path<'a>(proc_cgroup: &'a str, controller: &str) -> Option<&'a str> {
+    proc_cgroup.lines().find_map(|line| {
+        let mut fields = line.splitn(3, ':');
+        let (_, controllers, path) = (fields.next()?, fields.next()?, fields.next()?);
+
+        let matches = match controller {
+            "" => controllers.is_empty(),
+            c => controllers.split(',').any(|name| name == c),
+        };
+
+        matches.then_some(path)
+    })
+}
+
+/// The directory of the cgroup at `path` below `base`, followed by the
+/// directories of its parents, up to `base`.
+///
+/// Containers often mount their own cgroup at `base`, while
+/// `/proc/self/cgroup` still reports its path on the host. If the cgroup is
+/// not found below `base`, `base` itself is taken as the cgroup.
+fn cgroup_dirs(base: &Path, path: &str) -> Vec<PathBuf> {
+    let leaf = base.join(path.trim_start_matches('/'));
+
+    if !leaf.is_dir() {
+        return vec![base.to_path_buf()];
+    }
+
+    leaf.ancestors()
+        .take_while(|dir| dir.starts_with(base))
+        .map(Path::to_path_buf)
+        .collect()
+}
+
+/// Read the cpu quota of a cgroup v2 directory, from `cpu.max`.
+fn read_cpu_max(dir: &Path) -> Option<f32> {
+    let cpu_max = read_string(dir.join("cpu.max"))?;
+    let (quota, period) = cpu_max.split_once(' ')?;
+
+    Some(quota.parse::<f32>().ok()? / period.parse::<f32>().ok()?)
+}
+
+/// Read the cpu quota of a cgroup v1 cpu controller directory.
+fn read_cfs_quota(dir: &Path) -> Option<f32> {
+    let quota = read_value::<i64>(dir.join("cpu.cfs_quota_us")).filter(|q| *q > 0)?;
+
+    Some(quota as f32 / read_value::<f32>(dir.join("cpu.cfs_period_us"))?)
+}
+
+/// [`CgroupPoller`] reads the limits of the cgroup mainframe runs in, found
+/// through `<procfs root>/self/cgroup`, from the cgroup filesystem mounted at
+/// the cgroup root.
+pub struct CgroupPoller {
+    procfs_root: PathBuf,
+    cgroup_root: PathBuf,
+    /// Cumulative cpu time of the cgroup in µs, as of the last poll.
+    last_cpu_time: Option<(u64, Instant)>,
+}
+
+impl Default for CgroupPoller {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl CgroupPoller {
+    /// Create a new poller reading from `/proc`, and `/sys/fs/cgroup`.
+    pub fn new() -> Self {
+        Self::with_roots("/proc", "/sys/fs/cgroup")
+    }
+
+    /// Create a new poller reading from the given procfs, and cgroup roots.
+    pub fn with_roots(procfs_root: impl Into<PathBuf>, cgroup_root: impl Into<PathBuf>) -> Self {
+        CgroupPoller {
+            procfs_root: procfs_root.into(),
+            cgroup_root: cgroup_root.into(),
+            last_cpu_time: None,
+        }
+    }
+
+    /// Obtain a [`CgroupLimits`] reading, or `None` if cgroups are not
+    /// available.
+    ///
+    /// The hierarchy is taken to be cgroup v2 if the cgroup root holds a
+    /// unified hierarchy, and v1 otherwise, including hybrid setups which
+    /// mount an empty v2 hierarchy next to the v1 controllers.
+    pub fn poll(&mut self) -> Option<CgroupLimits> {
+        let proc_cgroup = read_string(self.procfs_root.join("self/cgroup"))?;
+
+        let (mut limits, cpu_time) = match self.cgroup_root.join("cgroup.controllers").exists() {
+            true => self.read_v2(&proc_cgroup)?,
+            false => self.read_v1(&proc_cgroup)?,
+        };
+
+        let now = Instant::now();
+
+        if let (Some(time), Some((last_time, last_now))) = (cpu_time, self.last_cpu_time) {
+            let elapsed = now.duration_since(last_now).as_secs_f32().max(f32::EPSILON);
+
+            limits.cpu_usage = Some(time.saturating_sub(last_time) as f32 / 1_000_000f32 / elapsed);
+        }
+
+        self.last_cpu_time = cpu_time.map(|t| (t, now));
+
+        Some(limits)
+    }
+
+    /// Read the limits of a cgroup v2 hierarchy, and the cumulative cpu time
+    /// of the cgroup in µs.
+    fn read_v2(&self, proc_cgroup: &str) -> Option<(CgroupLimits, Option<u64>)> {
+        let path = cgroup_path(proc_cgroup, "")?;
+        let dirs = cgroup_dirs(&self.cgroup_root, path);
+
+        let limits = CgroupLimits {
+            version: CgroupVersion::V2,
+            path: path.to_string(),
+            memory_limit: dirs
+                .iter()
+                .filter_map(|d| read_value(d.join("memory.max")))
+                .min(),
+            memory_usage: read_value(dirs[0].join("memory.current")),
+            cpu_quota: dirs.iter().filter_map(|d| read_cpu_max(d)).reduce(f32::min),
+            cpuset: dirs
+                .iter()
+                .find_map(|d| read_string(d.join("cpuset.cpus.effective")))
+                .map(|l| parse_cpu_list(&l))
+                .unwrap_or_default(),
+            cpu_usage: None,
+        };
+
+        let cpu_time = read_string(dirs[0].join("cpu.stat")).and_then(|stat| {
+            stat.lines()
+                .find_map(|l| l.strip_prefix("usage_usec "))?
+                .parse()
+                .ok()
+        });
+
+        Some((limits, cpu_time))
+    }
+
+    /// Read the limits of a cgroup v1 hierarchy, with one directory per
+    /// controller, and the cumulative cpu time of the cgroup in µs.
+    fn read_v1(&self, proc_cgroup: &str) -> Option<(CgroupLimits, Option<u64>)> {
+        let dirs = |controller: &str| match cgroup_path(proc_cgroup, controller) {
+            Some(path) => cgroup_dirs(&self.cgroup_root.join(controller), path),
+            None => vec![],
+        };
+
+        let memory = dirs("memory");
+        let cpuset = dirs("cpuset");
+        let cpuacct = dirs("cpuacct");
+
+        let limits = CgroupLimits {
+            version: CgroupVersion::V1,
+            path: cgroup_path(proc_cgroup, "memory")
+                .or_else(|| cgroup_path(proc_cgroup, "cpu"))?
+                .to_string(),
+            memory_limit: memory
+                .iter()
+                .filter_map(|d| read_value::<u64>(d.join("memory.limit_in_bytes")))
+                .filter(|l| *l < V1_UNLIMITED)
+                .min(),
+            memory_usage: memory
+                .first()
+                .and_then(|d| read_value(d.join("memory.usage_in_bytes"))),
+            cpu_quota: dirs("cpu")
+                .iter()
+                .filter_map(|d| read_cfs_quota(d))
+                .reduce(f32::min),
+            cpuset: cpuset
+                .first()
+                .and_then(|d| {
+                    read_string(d.join("cpuset.effective_cpus"))
+                        .or_else(|| read_string(d.join("cpuset.cpus")))
+                })
+                .map(|l| parse_cpu_list(&l))
+                .unwrap_or_default(),
+            cpu_usage: None,
+        };
+
+        // cpuacct reports cpu time in ns.
+        let cpu_time = cpuacct
+            .first()
+            .and_then(|d| read_value::<u64>(d.join("cpuacct.usage")))
+            .map(|t| t / 1000);
+
+        Some((limits, cpu_time))
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+
+    fn fixture_root(dir: &str) -> String {
+        format!("{}/tests/fixtures/{}", env!("CARGO_MANIFEST_DIR"), dir)
+    }
+
+    #[test]
+    fn test_cpu_list() {
+        assert!(parse_cpu_list("0-3,6") == [0, 1, 2, 3, 6]);
+        assert!(parse_cpu_list("").is_empty());
+        assert!(format_cpu_list(&[0, 1, 2, 3, 6, 8, 9]) == "0-3,6,8-9");
+    }
+
+    #[test]
+    fn test_poll_v2_fixture() {
+        let mut poller =
+            CgroupPoller::with_roots(fixture_root("proc"), fixture_root("sys/fs/cgroup"));
+
+        let limits = poller.poll().unwrap();
+
+        assert!(limits.version == CgroupVersion::V2);
+        assert!(limits.path == "/system.slice/docker-0123.scope");
+        assert!(limits.memory_limit == Some(512 * 1024 * 1024));
+        assert!(limits.memory_usage == Some(256 * 1024 * 1024));
+        assert!(limits.cpu_quota == Some(1.5));
+        assert!(limits.cpuset == [0, 1]);
+        assert!(limits.cpu_usage.is_none());
+
+        // The quota is tighter than the cpuset, which is tighter than the
+        // host.
+        assert!(limits.cpu_limit(8) == Some(1.5));
+        assert!(limits.cpuset_limited(8) && !limits.cpuset_limited(2));
+
+        // Nothing changed since the first poll.
+        assert!(poller.poll().unwrap().cpu_usage == Some(0.0));
+    }
+
+    #[test]
+    fn test_poll_v1_fixture() {
+        let mut poller = CgroupPoller::with_roots(
+            fixture_root("cgroup-v1/proc"),
+            fixture_root("cgroup-v1/sys/fs/cgroup"),
+        );
+
+        let limits = poller.poll().unwrap();
+
+        assert!(limits.version == CgroupVersion::V1);
+        assert!(limits.path == "/docker/4567");
+        assert!(limits.memory_limit == Some(1024 * 1024 * 1024));
+        assert!(limits.memory_usage == Some(100 * 1024 * 1024));
+        assert!(limits.cpu_quota.is_none());
+        assert!(limits.cpuset == [0, 2, 3]);
+        assert!(limits.cpu_limit(8) == Some(3.0));
+        assert!(limits.cpu_limit(3).is_none());
+    }
+
+    #[test]
+    fn test_poll_missing() {
+        assert!(CgroupPoller::with_roots("/nonexistent", "/nonexistent")
+            .poll()
+            .is_none());
+    }
+}
@@ -0,0 +1,346 @@
+/// Module cgroup_tree contains functionality to walk the cgroup v2 hierarchy,
+/// and poll the resource usage of every cgroup in it.
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+    time::Instant,
+};
+
+use super::{
+    cgroup::unified_root,
+    owner::{container_scope, short_container_id},
+    sysfs::{read_string, read_value},
+};
+
+/// [`CgroupNode`] contains the resource usage of a single cgroup, including
+/// all of its descendants.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct CgroupNode {
+    /// Path relative to the root of the hierarchy, such as
+    /// `/system.slice/cron.service`. The root cgroup is `/`.
+    pub path: String,
+    /// Name of the cgroup, with systemd escapes decoded.
+    pub name: String,
+    /// What the cgroup is, such as `service` or `session 3`, for cgroups
+    /// created by systemd, or container runtimes.
+    pub description: Option<String>,
+    /// Uid of the user, for user slices, and user managers.
+    pub uid: Option<u32>,
+    /// Number of ancestors between this cgroup and the root.
+    pub depth: usize,
+    pub has_children: bool,
+    /// Cpu usage since the previous poll, as a percentage of a single cpu.
+    /// `None` on the first poll.
+    pub cpu_usage: Option<f32>,
+    /// Memory used, including page cache, in bytes.
+    pub memory_current: Option<u64>,
+    /// Bytes read, and written per second since the previous poll.
+    pub io_read_per_sec: Option<f64>,
+    pub io_write_per_sec: Option<f64>,
+    /// Number of tasks in the cgroup.
+    pub pids_current: Option<u64>,
+}
+
+/// Decode the `\xNN` escapes systemd uses in unit names, such as in
+/// `system-systemd\x2dfsck.slice`.
+pub fn unescape_unit(name: &str) -> String {
+    let mut bytes = Vec::with_capacity(name.len());
+    let mut rest = name.as_bytes();
+
+    while !rest.is_empty() {
+        let decoded = rest
+            .strip_prefix(b"\\x")
+            .and_then(|r| std::str::from_utf8(r.get(..2)?).ok())
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+
+        match decoded {
+            Some(byte) => {
+                bytes.push(byte);
+                rest = &rest[4..];
+            }
+            None => {
+                bytes.push(rest[0]);
+                rest = &rest[1..];
+            }
+        }
+    }
+
+    String::from_utf8_lossy(&bytes).to_string()
+}
+
+/// Describe a cgroup created by systemd, or a container runtime, from its
+/// name.
+///
+/// Returns the description, and for user slices, and user managers, the uid
+/// of the user.
+pub fn describe_unit(name: &str) -> (Option<String>, Option<u32>) {
+    let between = |prefix: &str, suffix: &str| name.strip_prefix(prefix)?.strip_suffix(suffix);
+
+    if let Some(uid) = between("user-", ".slice").and_then(|u| u.parse().ok()) {
+        return (Some(format!("user {}", uid)), Some(uid));
+    }
+
+    if let Some(uid) = between("user@", ".service").and_then(|u| u.parse().ok()) {
+        return (Some(format!("user manager {}", uid)), Some(uid));
+    }
+
+    let description = if let Some(session) = between("session-", ".scope") {
+        format!("session {}", session)
+    } else if let Some((runtime, id)) = container_scope(name) {
+        format!("{} container {}", runtime, short_container_id(id))
+    } else if name.starts_with("kubepods") && name.ends_with(".slice") {
+        "kubernetes pods".to_string()
+    } else {
+        match name.rsplit_once('.') {
+            Some((_, kind @ ("service" | "slice" | "scope" | "mount" | "socket" | "swap"))) => {
+                kind.to_string()
+            }
+            _ => return (None, None),
+        }
+    };
+
+    (Some(description), None)
+}
+
+/// Sum the bytes read, and written across every device of an `io.stat` file.
+fn parse_io_stat(contents: &str) -> (u64, u64) {
+    contents
+        .split_whitespace()
+        .filter_map(|field| field.split_once('='))
+        .fold((0, 0), |(read, write), (key, value)| {
+            let value = value.parse().unwrap_or(0);
+
+            match key {
+                "rbytes" => (read + value, write),
+                "wbytes" => (read, write + value),
+                _ => (read, write),
+            }
+        })
+}
+
+/// Cumulative counters of a cgroup: cpu time in µs, and bytes read, and
+/// written.
+type CgroupCounters = (Option<u64>, Option<(u64, u64)>);
+
+/// [`CgroupTreePoller`] walks the cgroup v2 hierarchy found at the cgroup
+/// root, or in its `unified` directory on hybrid systems.
+///
+/// The usage reported by cgroup v2 includes all descendants, so it is not
+/// summed up here.
+pub struct CgroupTreePoller {
+    cgroup_root: PathBuf,
+    last_counters: HashMap<String, CgroupCounters>,
+    last_time: Instant,
+}
+
+impl Default for CgroupTreePoller {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl CgroupTreePoller {
+    /// Create a new poller reading from `/sys/fs/cgroup`.
+    pub fn new() -> Self {
+        Self::with_cgroup_root("/sys/fs/cgroup")
+    }
+
+    /// Create a new poller reading from the given cgroup root.
+    pub fn with_cgroup_root(root: impl Into<PathBuf>) -> Self {
+        CgroupTreePoller {
+            cgroup_root: root.into(),
+            last_counters: HashMap::new(),
+            last_time: Instant::now(),
+        }
+    }
+
+    /// Forget the counters of the previous poll, so that the next poll
+    /// reports no usage, as the first one does.
+    pub fn reset(&mut self) {
+        self.last_counters.clear();
+    }
+
+    /// Obtain a [`CgroupNode`] reading for every cgroup, with parents before
+    /// their children, and siblings ordered by name. Returns an empty list
+    /// if there is no cgroup v2 hierarchy.
+    pub fn poll(&mut self) -> Vec<CgroupNode> {
+        let Some(root) = unified_root(&self.cgroup_root) else {
+            return vec![];
+        };
+
+        let now = Instant::now();
+        let seconds = now
+            .duration_since(self.last_time)
+            .as_secs_f64()
+            .max(f64::EPSILON);
+
+        let mut nodes = vec![];
+        let mut counters = HashMap::new();
+
+        self.visit(
+            &root,
+            "/".to_string(),
+            0,
+            seconds,
+            &mut nodes,
+            &mut counters,
+        );
+
+        self.last_counters = counters;
+        self.last_time = now;
+
+        nodes
+    }
+
+    /// Read the cgroup at `dir`, and then its descendants, depth first.
+    fn visit(
+        &self,
+        dir: &Path,
+        path: String,
+        depth: usize,
+        seconds: f64,
+        nodes: &mut Vec<CgroupNode>,
+        counters: &mut HashMap<String, CgroupCounters>,
+    ) {
+        let mut children: Vec<(String, PathBuf)> = fs::read_dir(dir)
+            .map(|entries| {
+                entries
+                    .flatten()
+                    .filter(|e| e.file_type().is_ok_and(|t| t.is_dir()))
+                    .map(|e| (e.file_name().to_string_lossy().to_string(), e.path()))
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        children.sort();
+
+        let cpu_time: Option<u64> = read_string(dir.join("cpu.stat")).and_then(|stat| {
+            stat.lines()
+                .find_map(|l| l.strip_prefix("usage_usec "))?
+                .parse()
+                .ok()
+        });
+        let io = read_string(dir.join("io.stat")).map(|s| parse_io_stat(&s));
+
+        let (last_cpu_time, last_io) = self.last_counters.get(&path).copied().unwrap_or_default();
+
+        let rate = |current: Option<u64>, last: Option<u64>| {
+            Some(current?.saturating_sub(last?) as f64 / seconds)
+        };
+
+        let name = match path.as_str() {
+            "/" => "/".to_string(),
+            p => unescape_unit(p.rsplit('/').next().unwrap_or(p)),
+        };
+        let (description, uid) = describe_unit(&name);
+
+        nodes.push(CgroupNode {
+            path: path.clone(),
+            name,
+            description,
+            uid,
+            depth,
+            has_children: !children.is_empty(),
+            // Cpu time is in µs, so µs per second / 10^4 is a percentage.
+            cpu_usage: rate(cpu_time, last_cpu_time).map(|r| (r / 10_000f64) as f32),
+            memory_current: read_value(dir.join("memory.current")),
+            io_read_per_sec: rate(io.map(|io| io.0), last_io.map(|io| io.0)),
+            io_write_per_sec: rate(io.map(|io| io.1), last_io.map(|io| io.1)),
+            pids_current: read_value(dir.join("pids.current")),
+        });
+
+        counters.insert(path.clone(), (cpu_time, io));
+
+        for (name, child) in children {
+            let child_path = match path.as_str() {
+                "/" => format!("/{}", name),
+                p => format!("{}/{}", p, name),
+            };
+
+            self.visit(&child, child_path, depth + 1, seconds, nodes, counters);
+        }
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+
+    fn fixture_root() -> String {
+        format!(
+            "{}/tests/fixtures/sys/fs/cgroup",
+            env!("CARGO_MANIFEST_DIR")
+        )
+    }
+
+    #[test]
+    fn test_describe_unit() {
+        assert!(describe_unit("user-1000.slice") == (Some("user 1000".to_string()), Some(1000)));
+        assert!(describe_unit("session-3.scope").0.as_deref() == Some("session 3"));
+        assert!(describe_unit("cron.service").0.as_deref() == Some("service"));
+        assert!(
+            describe_unit("docker-0123456789abcdef.scope").0.as_deref()
+                == Some("docker container 0123456789ab")
+        );
+        assert!(describe_unit("init.scope").0.as_deref() == Some("scope"));
+        assert!(describe_unit("mygroup") == (None, None));
+    }
+
+    #[test]
+    fn test_unescape_unit() {
+        assert!(unescape_unit("system-systemd\\x2dfsck.slice") == "system-systemd-fsck.slice");
+        assert!(unescape_unit("trailing\\x") == "trailing\\x");
+    }
+
+    #[test]
+    fn test_parse_io_stat() {
+        assert!(
+            parse_io_stat("8:0 rbytes=10 wbytes=20 rios=1\n8:16 rbytes=5 wbytes=0\n") == (15, 20)
+        );
+    }
+
+    #[test]
+    fn test_poll_fixture() {
+        let mut poller = CgroupTreePoller::with_cgroup_root(fixture_root());
+
+        let nodes = poller.poll();
+
+        let paths: Vec<&str> = nodes.iter().map(|n| n.path.as_str()).collect();
+        assert!(
+            paths
+                == [
+                    "/",
+                    "/system.slice",
+                    "/system.slice/cron.service",
+                    "/system.slice/docker-0123.scope",
+                    "/system.slice/system-systemd\\x2dfsck.slice",
+                    "/user.slice",
+                    "/user.slice/user-1000.slice",
+                    "/user.slice/user-1000.slice/session-3.scope",
+                    "/user.slice/user-1000.slice/user@1000.service",
+                ]
+        );
+
+        let depths: Vec<usize> = nodes.iter().map(|n| n.depth).collect();
+        assert!(depths == [0, 1, 2, 2, 2, 1, 2, 3, 3]);
+        assert!(nodes[0].has_children && !nodes[2].has_children);
+
+        let system = &nodes[1];
+        assert!(system.memory_current == Some(1024 * 1024 * 1024));
+        assert!(system.pids_current == Some(42));
+        assert!(system.cpu_usage.is_none() && system.io_read_per_sec.is_none());
+
+        assert!(nodes[4].name == "system-systemd-fsck.slice");
+        assert!(nodes[6].uid == Some(1000));
+
+        // Nothing changed since the first poll.
+        let nodes = poller.poll();
+        assert!(nodes[1].cpu_usage == Some(0.0));
+        assert!(nodes[1].io_write_per_sec == Some(0.0));
+        assert!(nodes[2].io_write_per_sec.is_none());
+
+        poller.reset();
+        assert!(poller.poll()[1].cpu_usage.is_none());
+    }
+}
@@ -0,0 +1,119 @@
+/// Module cpufreq contains functionality to poll the frequency, and frequency
+/// scaling policy of each logical cpu through cpufreq sysfs.
+use std::{fs, path::PathBuf};
+
+use super::sysfs::{read_string, read_value};
+
+/// [`CpuFrequency`] contains the frequency, and scaling policy of a logical
+/// cpu. All frequencies are in MHz.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct CpuFrequency {
+    /// Index of the logical cpu, as in `cpu0`.
+    pub cpu: usize,
+    pub current: u32,
+    /// Lowest frequency allowed by the scaling policy.
+    pub min: u32,
+    /// Highest frequency allowed by the scaling policy.
+    pub max: u32,
+    /// Highest frequency supported by the hardware, including turbo.
+    pub hardware_max: u32,
+    /// Scaling governor, such as `powersave` or `performance`.
+    pub governor: Option<String>,
+    /// Energy performance preference, such as `balance_performance`. Only
+    /// reported by drivers which let the hardware choose the frequency, such
+    /// as intel_pstate and amd-pstate.
+    pub energy_performance_preference: Option<String>,
+}
+
+/// [`CpuFrequencyPoller`] reads the cpufreq policy of every logical cpu found
+/// under `<sysfs root>/devices/system/cpu`.
+///
+/// Cpus without cpufreq support, as is common in virtual machines, are
+/// skipped.
+pub struct CpuFrequencyPoller {
+    sysfs_root: PathBuf,
+}
+
+impl Default for CpuFrequencyPoller {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl CpuFrequencyPoller {
+    /// Create a new poller reading from `/sys`.
+    pub fn new() -> Self {
+        Self::with_sysfs_root("/sys")
+    }
+
+    /// Create a new poller reading from the given sysfs root.
+    pub fn with_sysfs_root(root: impl Into<PathBuf>) -> Self {
+        CpuFrequencyPoller {
+            sysfs_root: root.into(),
+        }
+    }
+
+    /// Obtain a [`CpuFrequency`] reading for each logical cpu, ordered by
+    /// index.
+    pub fn poll(&self) -> Vec<CpuFrequency> {
+        let Ok(entries) = fs::read_dir(self.sysfs_root.join("devices/system/cpu")) else {
+            return vec![];
+        };
+
+        let mut cpus: Vec<CpuFrequency> = entries
+            .flatten()
+            .filter_map(|e| {
+                let cpu = e.file_name().to_str()?.strip_prefix("cpu")?.parse().ok()?;
+                let path = e.path().join("cpufreq");
+
+                // cpufreq reports frequencies in kHz.
+                let mhz = |file: &str| read_value::<u32>(path.join(file)).map(|f| f / 1000);
+
+                let current = mhz("scaling_cur_freq")?;
+                let max = mhz("scaling_max_freq").unwrap_or(current);
+
+                Some(CpuFrequency {
+                    cpu,
+                    current,
+                    min: mhz("scaling_min_freq").unwrap_or(0),
+                    max,
+                    hardware_max: mhz("cpuinfo_max_freq").unwrap_or(max),
+                    governor: read_string(path.join("scaling_governor")),
+                    energy_performance_preference: read_string(
+                        path.join("energy_performance_preference"),
+                    ),
+                })
+            })
+            .collect();
+
+        cpus.sort_by_key(|c| c.cpu);
+
+        cpus
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+
+    fn fixture_root() -> String {
+        format!("{}/tests/fixtures/sys", env!("CARGO_MANIFEST_DIR"))
+    }
+
+    #[test]
+    fn test_poll_fixture() {
+        let cpus = CpuFrequencyPoller::with_sysfs_root(fixture_root()).poll();
+
+        // cpu2 has no cpufreq directory.
+        assert!(cpus.iter().map(|c| c.cpu).collect::<Vec<_>>() == [0, 1]);
+
+        assert!(cpus[0].current == 3400);
+        assert!(cpus[0].min == 800 && cpus[0].max == 4600);
+        assert!(cpus[0].governor.as_deref() == Some("powersave"));
+        assert!(cpus[0].energy_performance_preference.as_deref() == Some("balance_performance"));
+
+        // cpu1 is capped below its hardware maximum.
+        assert!(cpus[1].max == 3000 && cpus[1].hardware_max == 4600);
+        assert!(cpus[1].energy_performance_preference.is_none());
+    }
+}
@@ -0,0 +1,270 @@
+/// Module diskio contains functionality to poll block device I/O statistics
+/// from `/proc/diskstats`.
+use std::{collections::HashMap, fs, path::PathBuf, time::Instant};
+
+/// Size in bytes of the sectors counted by `/proc/diskstats`, regardless of
+/// the device's actual sector size.
+static SECTOR_SIZE: u64 = 512;
+
+/// [`DiskCounters`] holds the cumulative I/O counters of a block device, as
+/// read from one line of `/proc/diskstats`.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct DiskCounters {
+    pub reads: u64,
+    pub sectors_read: u64,
+    pub read_ms: u64,
+    pub writes: u64,
+    pub sectors_written: u64,
+    pub write_ms: u64,
+    /// Requests currently in flight. Unlike the other fields, this is not
+    /// cumulative.
+    pub in_flight: u64,
+    /// Time during which at least one request was in flight.
+    pub io_ms: u64,
+    /// Time spent on all requests, weighted by the number in flight.
+    pub weighted_io_ms: u64,
+}
+
+/// Parse the contents of `/proc/diskstats` into the counters of each device.
+///
+/// Lines which are too short to contain the basic counters are skipped.
+pub fn parse_diskstats(contents: &str) -> Vec<(String, DiskCounters)> {
+    contents
+        .lines()
+        .filter_map(|line| {
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            let field = |i: usize| fields.get(i)?.parse::<u64>().ok();
+
+            Some((
+                fields.get(2)?.to_string(),
+                DiskCounters {
+                    reads: field(3)?,
+                    sectors_read: field(5)?,
+                    read_ms: field(6)?,
+                    writes: field(7)?,
+                    sectors_written: field(9)?,
+                    write_ms: field(10)?,
+                    in_flight: field(11)?,
+                    io_ms: field(12)?,
+                    weighted_io_ms: field(13)?,
+                },
+            ))
+        })
+        .collect()
+}
+
+/// [`DiskIoPollResult`] contains the I/O activity of a block device between
+/// two polls.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct DiskIoPollResult {
+    pub name: String,
+    /// Names of the partitions of this device. Their I/O is included in the
+    /// device's own figures.
+    pub partitions: Vec<String>,
+    pub read_bytes_per_sec: f64,
+    pub write_bytes_per_sec: f64,
+    pub read_iops: f64,
+    pub write_iops: f64,
+    /// Average time to complete a read, in milliseconds, or `None` if no
+    /// reads completed.
+    pub read_latency_ms: Option<f64>,
+    /// Average time to complete a write, in milliseconds, or `None` if no
+    /// writes completed.
+    pub write_latency_ms: Option<f64>,
+    /// Average number of requests in flight.
+    pub queue_depth: f64,
+    /// Percentage of time during which the device was busy.
+    pub utilization: f64,
+}
+
+impl DiskIoPollResult {
+    /// Compute the I/O activity of a device from two samples of its counters,
+    /// taken `seconds` apart.
+    pub fn from_counters(
+        name: String,
+        previous: &DiskCounters,
+        current: &DiskCounters,
+        seconds: f64,
+    ) -> Self {
+        let seconds = seconds.max(f64::EPSILON);
+        let delta = |f: fn(&DiskCounters) -> u64| f(current).saturating_sub(f(previous));
+
+        let (reads, writes) = (delta(|c| c.reads), delta(|c| c.writes));
+        let latency = |ms: u64, requests: u64| match requests {
+            0 => None,
+            n => Some(ms as f64 / n as f64),
+        };
+
+        DiskIoPollResult {
+            name,
+            partitions: vec![],
+            read_bytes_per_sec: (delta(|c| c.sectors_read) * SECTOR_SIZE) as f64 / seconds,
+            write_bytes_per_sec: (delta(|c| c.sectors_written) * SECTOR_SIZE) as f64 / seconds,
+            read_iops: reads as f64 / seconds,
+            write_iops: writes as f64 / seconds,
+            read_latency_ms: latency(delta(|c| c.read_ms), reads),
+            write_latency_ms: latency(delta(|c| c.write_ms), writes),
+            queue_depth: delta(|c| c.weighted_io_ms) as f64 / (seconds * 1000f64),
+            utilization: (100f64 * delta(|c| c.io_ms) as f64 / (seconds * 1000f64)).min(100f64),
+        }
+    }
+}
+
+/// [`DiskIoPoller`] reads the I/O counters of every block device from
+/// `<procfs root>/diskstats`, and tells partitions apart from whole devices
+/// through `<sysfs root>/class/block`.
+///
+/// Partitions are rolled up into their parent device, and devices which have
+/// never completed a request, such as unused loop devices, are skipped.
+///
+/// Since activity is computed from the difference between two polls, the
+/// first poll always reports no activity.
+pub struct DiskIoPoller {
+    sysfs_root: PathBuf,
+    procfs_root: PathBuf,
+    last_counters: HashMap<String, DiskCounters>,
+    last_time: Instant,
+}
+
+impl Default for DiskIoPoller {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl DiskIoPoller {
+    /// Create a new poller reading from `/sys` and `/proc`.
+    pub fn new() -> Self {
+        Self::with_roots("/sys", "/proc")
+    }
+
+    /// Create a new poller reading from the given sysfs and procfs roots.
+    pub fn with_roots(sysfs_root: impl Into<PathBuf>, procfs_root: impl Into<PathBuf>) -> Self {
+        DiskIoPoller {
+            sysfs_root: sysfs_root.into(),
+            procfs_root: procfs_root.into(),
+            last_counters: HashMap::new(),
+            last_time: Instant::now(),
+        }
+    }
+
+    /// Obtain [`DiskIoPollResult`] readings for all block devices, in the
+    /// order in which the kernel lists them.
+    pub fn poll(&mut self) -> Vec<DiskIoPollResult> {
+        let Ok(contents) = fs::read_to_string(self.procfs_root.join("diskstats")) else {
+            return vec![];
+        };
+
+        let now = Instant::now();
+        let seconds = now.duration_since(self.last_time).as_secs_f64();
+
+        let stats = parse_diskstats(&contents);
+
+        let (partitions, devices): (Vec<_>, Vec<_>) = stats.iter().partition(|(name, _)| {
+            self.sysfs_root
+                .join("class/block")
+                .join(name)
+                .join("partition")
+                .exists()
+        });
+
+        let mut results: Vec<DiskIoPollResult> = devices
+            .iter()
+            .filter(|(_, counters)| counters.reads + counters.writes > 0)
+            .map(|(name, counters)| {
+                let previous = self.last_counters.get(name).unwrap_or(counters);
+
+                DiskIoPollResult::from_counters(name.clone(), previous, counters, seconds)
+            })
+            .collect();
+
+        for (partition, _) in partitions {
+            let Some(name) = self.parent_device(partition) else {
+                continue;
+            };
+
+            if let Some(parent) = results.iter_mut().find(|d| d.name == name) {
+                parent.partitions.push(partition.clone());
+            }
+        }
+
+        self.last_counters = stats.into_iter().collect();
+        self.last_time = now;
+
+        results
+    }
+
+    /// Find the name of the device a partition belongs to. In sysfs, a
+    /// partition's directory sits inside the directory of its device, which
+    /// `class/block` links to.
+    fn parent_device(&self, partition: &str) -> Option<String> {
+        let path = fs::canonicalize(self.sysfs_root.join("class/block").join(partition)).ok()?;
+
+        Some(path.parent()?.file_name()?.to_string_lossy().to_string())
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+
+    fn fixture_root(dir: &str) -> String {
+        format!("{}/tests/fixtures/{}", env!("CARGO_MANIFEST_DIR"), dir)
+    }
+
+    #[test]
+    fn test_parse_diskstats() {
+        let stats = parse_diskstats(
+            " 259 0 nvme0n1 200000 1000 8000000 40000 100000 2000 4000000 120000 2 90000 160000 0 0 0 0\n 8 0 short 1 2 3\n",
+        );
+
+        assert!(stats.len() == 1);
+        assert!(stats[0].0 == "nvme0n1");
+        assert!(stats[0].1.reads == 200000);
+        assert!(stats[0].1.sectors_written == 4000000);
+        assert!(stats[0].1.in_flight == 2);
+        assert!(stats[0].1.weighted_io_ms == 160000);
+    }
+
+    #[test]
+    fn test_from_counters() {
+        let previous = DiskCounters::default();
+        let current = DiskCounters {
+            reads: 200,
+            sectors_read: 4096,
+            read_ms: 100,
+            writes: 0,
+            io_ms: 500,
+            weighted_io_ms: 1500,
+            ..Default::default()
+        };
+
+        let io = DiskIoPollResult::from_counters("sda".to_string(), &previous, &current, 2.0);
+
+        assert!(io.read_bytes_per_sec == 1048576.0);
+        assert!(io.read_iops == 100.0);
+        assert!(io.read_latency_ms == Some(0.5));
+        assert!(io.write_latency_ms.is_none());
+        assert!(io.queue_depth == 0.75);
+        assert!(io.utilization == 25.0);
+    }
+
+    #[test]
+    fn test_poll_fixture() {
+        let mut poller = DiskIoPoller::with_roots(fixture_root("sys"), fixture_root("proc"));
+
+        let devices = poller.poll();
+
+        // Partitions roll up into nvme0n1, and the idle loop device is
+        // skipped.
+        assert!(devices.iter().map(|d| d.name.as_str()).collect::<Vec<_>>() == ["nvme0n1", "sda"]);
+        assert!(devices[0].partitions == ["nvme0n1p1", "nvme0n1p2"]);
+        assert!(devices[1].partitions.is_empty());
+        assert!(poller.parent_device("nvme0n1p2").as_deref() == Some("nvme0n1"));
+        assert!(poller.parent_device("sdb1").is_none());
+
+        // Nothing changed since the first poll.
+        assert!(devices[0].read_iops == 0.0);
+        assert!(devices[0].read_latency_ms.is_none());
+    }
+}
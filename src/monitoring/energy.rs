@@ -0,0 +1,125 @@
+/// Module energy contains functionality to track the energy used by the
+/// machine over a session, and estimate its cost, and carbon emissions.
+use std::time::Instant;
+
+/// [`SessionEnergy`] contains the energy used by the machine over a period
+/// of time.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct SessionEnergy {
+    pub energy_wh: f64,
+    pub elapsed_seconds: f64,
+}
+
+impl SessionEnergy {
+    /// Energy used since an earlier reading of the same session, such as
+    /// the reading at which the user reset the session.
+    pub fn since(&self, baseline: &SessionEnergy) -> SessionEnergy {
+        SessionEnergy {
+            energy_wh: (self.energy_wh - baseline.energy_wh).max(0f64),
+            elapsed_seconds: (self.elapsed_seconds - baseline.elapsed_seconds).max(0f64),
+        }
+    }
+}
+
+/// [`EnergyRates`] contains the electricity price, and carbon intensity used
+/// to estimate the cost, and emissions of the energy used.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct EnergyRates {
+    /// Price of a kWh, in the user's currency.
+    pub price_per_kwh: Option<f64>,
+    /// Grams of CO2 emitted per kWh.
+    pub co2_grams_per_kwh: Option<f64>,
+}
+
+impl EnergyRates {
+    pub fn cost(&self, energy_wh: f64) -> Option<f64> {
+        Some(self.price_per_kwh? * energy_wh / 1000f64)
+    }
+
+    pub fn co2_grams(&self, energy_wh: f64) -> Option<f64> {
+        Some(self.co2_grams_per_kwh? * energy_wh / 1000f64)
+    }
+}
+
+/// [`EnergyMeter`] integrates power readings over time into the energy used
+/// since the meter was created.
+pub struct EnergyMeter {
+    start: Instant,
+    last: Instant,
+    energy_wh: f64,
+}
+
+impl Default for EnergyMeter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl EnergyMeter {
+    pub fn new() -> Self {
+        Self::starting_at(Instant::now())
+    }
+
+    fn starting_at(start: Instant) -> Self {
+        EnergyMeter {
+            start,
+            last: start,
+            energy_wh: 0f64,
+        }
+    }
+
+    /// Add a power reading, in watts, taken at `now`.
+    ///
+    /// Readings are averages over the time since the previous reading, so
+    /// that whole span is counted at this power. Time without a reading is
+    /// not counted.
+    pub fn add(&mut self, watts: Option<f32>, now: Instant) -> SessionEnergy {
+        let hours = now.duration_since(self.last).as_secs_f64() / 3600f64;
+
+        self.energy_wh += watts.unwrap_or(0f32) as f64 * hours;
+        self.last = now;
+
+        SessionEnergy {
+            energy_wh: self.energy_wh,
+            elapsed_seconds: now.duration_since(self.start).as_secs_f64(),
+        }
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn test_energy_meter() {
+        let start = Instant::now();
+        let mut meter = EnergyMeter::starting_at(start);
+
+        meter.add(Some(100.0), start + Duration::from_secs(1800));
+        meter.add(None, start + Duration::from_secs(3600));
+        let energy = meter.add(Some(20.0), start + Duration::from_secs(5400));
+
+        assert!(energy.energy_wh == 60.0);
+        assert!(energy.elapsed_seconds == 5400.0);
+
+        let baseline = SessionEnergy {
+            energy_wh: 50.0,
+            elapsed_seconds: 3600.0,
+        };
+
+        assert!(energy.since(&baseline).energy_wh == 10.0);
+        assert!(energy.since(&baseline).elapsed_seconds == 1800.0);
+    }
+
+    #[test]
+    fn test_energy_rates() {
+        let rates = EnergyRates {
+            price_per_kwh: Some(0.30),
+            co2_grams_per_kwh: None,
+        };
+
+        assert!(rates.cost(500.0).is_some_and(|c| (c - 0.15).abs() < 1e-9));
+        assert!(rates.co2_grams(500.0).is_none());
+    }
+}
@@ -0,0 +1,98 @@
+/// Module filesystem contains functionality to read the usage of mounted
+/// filesystems.
+use std::{ffi::CString, os::unix::ffi::OsStrExt, path::Path};
+
+use super::system::DiskInformation;
+
+/// [`FilesystemStats`] holds the statistics of a mounted filesystem which
+/// are not reported by sysinfo.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct FilesystemStats {
+    pub inodes_total: u64,
+    pub inodes_free: u64,
+    pub read_only: bool,
+}
+
+/// Read the inode counts, and mount flags of the filesystem mounted at
+/// `mount_point`.
+///
+/// Returns `None` if the filesystem could not be queried.
+pub fn filesystem_stats(mount_point: &Path) -> Option<FilesystemStats> {
+    let path = CString::new(mount_point.as_os_str().as_bytes()).ok()?;
+
+    // SAFETY: statvfs is plain old data, which is fully written by a
+    // successful call.
+    let mut stats: libc::statvfs = unsafe { std::mem::zeroed() };
+
+    // SAFETY: path is a valid nul terminated string, and stats a valid
+    // statvfs struct.
+    if unsafe { libc::statvfs(path.as_ptr(), &mut stats) } != 0 {
+        return None;
+    }
+
+    Some(FilesystemStats {
+        inodes_total: stats.f_files as u64,
+        inodes_free: stats.f_ffree as u64,
+        read_only: stats.f_flag & libc::ST_RDONLY != 0,
+    })
+}
+
+/// Construct a disk data object for each mounted filesystem, ordered by
+/// mount point.
+///
+/// Virtual filesystems, such as proc and tmpfs, are skipped.
+pub fn read_disks() -> Vec<DiskInformation> {
+    let mut disks: Vec<DiskInformation> = sysinfo::Disks::new_with_refreshed_list()
+        .iter()
+        .map(|disk| {
+            let stats = filesystem_stats(disk.mount_point()).unwrap_or_default();
+
+            DiskInformation {
+                name: disk.name().to_string_lossy().to_string(),
+                kind: match disk.kind() {
+                    sysinfo::DiskKind::SSD => "SSD".to_string(),
+                    sysinfo::DiskKind::HDD => "HDD".to_string(),
+                    sysinfo::DiskKind::Unknown(s) => format!("??? ({})", s),
+                },
+                mount_point: disk.mount_point().to_string_lossy().to_string(),
+                file_system: disk.file_system().to_string_lossy().to_string(),
+                available_space: disk.available_space(),
+                total_space: disk.total_space(),
+                inodes_total: stats.inodes_total,
+                inodes_free: stats.inodes_free,
+                read_only: stats.read_only,
+            }
+        })
+        .collect();
+
+    disks.sort_by(|a, b| a.mount_point.cmp(&b.mount_point));
+
+    disks
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+
+    #[test]
+    fn test_filesystem_stats() {
+        let stats = filesystem_stats(Path::new("/")).unwrap();
+
+        assert!(stats.inodes_free <= stats.inodes_total);
+        assert!(filesystem_stats(Path::new("/does/not/exist")).is_none());
+    }
+
+    #[test]
+    fn test_used_space() {
+        let disk = DiskInformation {
+            available_space: 300,
+            total_space: 1000,
+            inodes_total: 50,
+            inodes_free: 20,
+            ..Default::default()
+        };
+
+        assert!(disk.used_space() == 700);
+        assert!(disk.inodes_used() == 30);
+    }
+}
@@ -0,0 +1,172 @@
+/// Module amd contains functionality to poll AMD gpus through the sysfs
+/// interface exposed by the `amdgpu` kernel driver.
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use crate::monitoring::polling::GpuPollResult;
+use crate::monitoring::sysfs::{find_hwmon, read_string, read_value};
+
+use super::{GpuBackend, GpuError};
+
+/// PCI vendor id of Advanced Micro Devices.
+static AMD_VENDOR_ID: &str = "0x1002";
+
+/// [`AmdGpuBackend`] reads gpu metrics for every AMD card found under
+/// `<sysfs root>/class/drm`.
+///
+/// Cards are rediscovered on every poll, so hot-plugged devices show up
+/// without a restart.
+///
+/// The sysfs root defaults to `/sys`, and can be changed with
+/// [`AmdGpuBackend::with_sysfs_root()`], which is useful for testing against a
+/// fixture directory.
+pub struct AmdGpuBackend {
+    sysfs_root: PathBuf,
+    cards: Vec<(String, PathBuf)>,
+}
+
+impl Default for AmdGpuBackend {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl AmdGpuBackend {
+    /// Create a new backend reading from `/sys`.
+    pub fn new() -> Self {
+        Self::with_sysfs_root("/sys")
+    }
+
+    /// Create a new backend reading from the given sysfs root.
+    pub fn with_sysfs_root(root: impl Into<PathBuf>) -> Self {
+        AmdGpuBackend {
+            sysfs_root: root.into(),
+            cards: vec![],
+        }
+    }
+
+    /// Find the card name, and device directory of every AMD card, ordered by
+    /// card number.
+    fn find_cards(&self) -> Vec<(String, PathBuf)> {
+        let Ok(entries) = fs::read_dir(self.sysfs_root.join("class/drm")) else {
+            return vec![];
+        };
+
+        let mut cards: Vec<(String, PathBuf)> = entries
+            .flatten()
+            .map(|e| (e.file_name().to_string_lossy().to_string(), e.path()))
+            // Skip connectors, such as card0-DP-1, and render nodes.
+            .filter(|(name, _)| {
+                name.strip_prefix("card")
+                    .is_some_and(|n| !n.is_empty() && n.chars().all(|c| c.is_ascii_digit()))
+            })
+            .map(|(name, path)| (name, path.join("device")))
+            .filter(|(_, device)| {
+                read_string(device.join("vendor")).as_deref() == Some(AMD_VENDOR_ID)
+            })
+            .collect();
+
+        cards.sort_by_key(|(name, _)| name[4..].parse::<u32>().unwrap_or(0));
+
+        cards
+    }
+
+    fn poll_card(card: &str, device: &Path) -> Result<GpuPollResult, GpuError> {
+        // The card may have been removed since it was discovered.
+        if !device.exists() {
+            return Err(GpuError::new("amdgpu", format!("{} disappeared", card)));
+        }
+
+        let hwmon = find_hwmon(device);
+        let hwmon_value = |file: &str| hwmon.as_ref().and_then(|h| read_value::<f32>(h.join(file)));
+
+        let name = read_string(device.join("product_name"))
+            .filter(|n| !n.is_empty())
+            .unwrap_or_else(|| {
+                format!(
+                    "AMD GPU {} ({})",
+                    read_string(device.join("device")).unwrap_or_default(),
+                    card
+                )
+            });
+
+        let fan_speed = hwmon_value("pwm1").map(|pwm| {
+            let max = hwmon_value("pwm1_max").unwrap_or(255f32);
+            (100f32 * pwm / max).round() as u32
+        });
+
+        Ok(GpuPollResult {
+            name,
+            // hwmon reports temperatures in millidegrees celsius.
+            temp: hwmon_value("temp1_input").map_or(0f32, |t| t / 1000f32),
+            usage: read_value(device.join("gpu_busy_percent")).unwrap_or(0f32),
+            memory_total: read_value(device.join("mem_info_vram_total")).unwrap_or(0),
+            memory_used: read_value(device.join("mem_info_vram_used")).unwrap_or(0),
+            // hwmon reports power in microwatts. Newer kernels only expose
+            // power1_input, older ones only power1_average.
+            power_usage: hwmon_value("power1_average")
+                .or_else(|| hwmon_value("power1_input"))
+                .map(|p| p / 1_000_000f32),
+            power_limit: hwmon_value("power1_cap").map(|p| p / 1_000_000f32),
+            fan_speed,
+            ..Default::default()
+        })
+    }
+}
+
+impl GpuBackend for AmdGpuBackend {
+    fn name(&self) -> &str {
+        "amdgpu"
+    }
+
+    fn discover(&mut self) -> Result<usize, GpuError> {
+        self.cards = self.find_cards();
+
+        Ok(self.cards.len())
+    }
+
+    fn poll_device(&mut self, index: usize) -> Result<GpuPollResult, GpuError> {
+        match self.cards.get(index) {
+            Some((card, device)) => Self::poll_card(card, device),
+            None => Err(GpuError::new(self.name(), format!("no device {}", index))),
+        }
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::AmdGpuBackend;
+    use crate::monitoring::gpu::{poll_backends, GpuBackend};
+
+    fn fixture_root() -> String {
+        format!("{}/tests/fixtures/sys", env!("CARGO_MANIFEST_DIR"))
+    }
+
+    #[test]
+    fn test_poll_fixture() {
+        let mut backends: Vec<Box<dyn GpuBackend>> =
+            vec![Box::new(AmdGpuBackend::with_sysfs_root(fixture_root()))];
+        let (gpus, errors) = poll_backends(&mut backends);
+
+        assert!(errors.is_empty());
+
+        // The fixture contains one AMD card, one Intel card, and a connector.
+        assert!(gpus.len() == 1);
+
+        let gpu = &gpus[0];
+        assert!(gpu.name == "AMD Radeon RX 6800 XT");
+        assert!(gpu.usage == 42.0);
+        assert!(gpu.temp == 54.0);
+        assert!(gpu.memory_used == 2147483648);
+        assert!(gpu.memory_total == 17163091968);
+        assert!(gpu.power_usage == Some(187.0));
+        assert!(gpu.fan_speed == Some(40));
+    }
+
+    #[test]
+    fn test_missing_root() {
+        assert!(AmdGpuBackend::with_sysfs_root("/does/not/exist").discover() == Ok(0));
+    }
+}
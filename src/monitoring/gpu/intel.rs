@@ -0,0 +1,442 @@
+/// Module intel contains functionality to poll Intel gpus through the
+/// `i915` and `xe` kernel drivers.
+///
+/// Engine utilization is computed from the per-client DRM usage statistics
+/// found in `/proc/<pid>/fdinfo`, and frequency and temperature are read from
+/// sysfs. None of these require root, though without it only the clients of
+/// processes owned by the current user are visible.
+use std::{collections::HashMap, fs, path::PathBuf, time::Instant};
+
+use crate::monitoring::polling::GpuPollResult;
+use crate::monitoring::sysfs::{find_hwmon, read_string, read_value};
+
+use super::{GpuBackend, GpuError};
+
+/// PCI vendor id of Intel.
+static INTEL_VENDOR_ID: &str = "0x8086";
+
+/// Engine usage counters of a single DRM client, as reported in fdinfo.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct DrmClientUsage {
+    pub driver: String,
+    pub pdev: String,
+    pub client_id: String,
+    /// Busy time in nanoseconds, per engine (i915).
+    pub engine_ns: HashMap<String, u64>,
+    /// Busy cycles, and total elapsed cycles, per engine class (xe).
+    pub engine_cycles: HashMap<String, (u64, u64)>,
+}
+
+/// Parse the contents of a `/proc/<pid>/fdinfo/<fd>` file.
+///
+/// Returns `None` if the file does not describe a DRM client.
+pub fn parse_fdinfo(contents: &str) -> Option<DrmClientUsage> {
+    let mut usage = DrmClientUsage::default();
+    let mut total_cycles = HashMap::<String, u64>::new();
+
+    for line in contents.lines() {
+        let Some((key, value)) = line.split_once(':') else {
+            continue;
+        };
+
+        let value = value.trim();
+        let number = || value.split_whitespace().next()?.parse::<u64>().ok();
+
+        match key {
+            "drm-driver" => usage.driver = value.to_string(),
+            "drm-pdev" => usage.pdev = value.to_string(),
+            "drm-client-id" => usage.client_id = value.to_string(),
+            k => {
+                if let (Some(engine), Some(n)) = (k.strip_prefix("drm-engine-"), number()) {
+                    // drm-engine-capacity-<engine> reports an engine count,
+                    // rather than a busy time.
+                    if !engine.starts_with("capacity-") {
+                        usage.engine_ns.insert(engine.to_string(), n);
+                    }
+                } else if let (Some(class), Some(n)) =
+                    (k.strip_prefix("drm-total-cycles-"), number())
+                {
+                    total_cycles.insert(class.to_string(), n);
+                } else if let (Some(class), Some(n)) = (k.strip_prefix("drm-cycles-"), number()) {
+                    usage.engine_cycles.entry(class.to_string()).or_default().0 = n;
+                }
+            }
+        }
+    }
+
+    for (class, total) in total_cycles {
+        usage.engine_cycles.entry(class).or_default().1 = total;
+    }
+
+    match usage.driver.is_empty() || usage.client_id.is_empty() {
+        true => None,
+        false => Some(usage),
+    }
+}
+
+/// Compute the utilization percentage of the busiest engine of a device,
+/// between two samples of its clients, taken `elapsed_ns` apart.
+///
+/// Only clients present in both samples contribute, since a client's first
+/// sample has no reference point.
+pub fn engine_utilization(
+    previous: &[DrmClientUsage],
+    current: &[DrmClientUsage],
+    elapsed_ns: u64,
+) -> f32 {
+    let mut busy_ns = HashMap::<&str, u64>::new();
+    let mut busy_cycles = HashMap::<&str, (u64, u64)>::new();
+
+    for c in current {
+        let Some(p) = previous.iter().find(|p| p.client_id == c.client_id) else {
+            continue;
+        };
+
+        for (engine, ns) in &c.engine_ns {
+            let delta = ns.saturating_sub(*p.engine_ns.get(engine).unwrap_or(ns));
+            *busy_ns.entry(engine).or_default() += delta;
+        }
+
+        for (class, (cycles, total)) in &c.engine_cycles {
+            let (p_cycles, p_total) = p
+                .engine_cycles
+                .get(class)
+                .copied()
+                .unwrap_or((*cycles, *total));
+            let entry = busy_cycles.entry(class).or_default();
+
+            entry.0 += cycles.saturating_sub(p_cycles);
+            entry.1 = entry.1.max(total.saturating_sub(p_total));
+        }
+    }
+
+    let from_ns = busy_ns
+        .values()
+        .map(|ns| 100f32 * *ns as f32 / elapsed_ns.max(1) as f32);
+
+    let from_cycles = busy_cycles
+        .values()
+        .filter(|(_, total)| *total > 0)
+        .map(|(cycles, total)| 100f32 * *cycles as f32 / *total as f32);
+
+    from_ns.chain(from_cycles).fold(0f32, f32::max).min(100f32)
+}
+
+/// [`IntelGpuBackend`] reads gpu metrics for every Intel card found under
+/// `<sysfs root>/class/drm`, and the DRM clients found under
+/// `<procfs root>`.
+///
+/// Since utilization is computed from the difference between two polls, the
+/// first poll always reports 0% usage.
+pub struct IntelGpuBackend {
+    sysfs_root: PathBuf,
+    procfs_root: PathBuf,
+    /// Cards found by the last discovery, and their utilization.
+    cards: Vec<(IntelCard, f32)>,
+    last_clients: HashMap<String, Vec<DrmClientUsage>>,
+    last_time: Instant,
+}
+
+impl Default for IntelGpuBackend {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl IntelGpuBackend {
+    /// Create a new backend reading from `/sys` and `/proc`.
+    pub fn new() -> Self {
+        Self::with_roots("/sys", "/proc")
+    }
+
+    /// Create a new backend reading from the given sysfs and procfs roots.
+    pub fn with_roots(sysfs_root: impl Into<PathBuf>, procfs_root: impl Into<PathBuf>) -> Self {
+        IntelGpuBackend {
+            sysfs_root: sysfs_root.into(),
+            procfs_root: procfs_root.into(),
+            cards: vec![],
+            last_clients: HashMap::new(),
+            last_time: Instant::now(),
+        }
+    }
+
+    /// Find every Intel card driven by i915 or xe, ordered by card number.
+    fn find_cards(&self) -> Vec<IntelCard> {
+        let Ok(entries) = fs::read_dir(self.sysfs_root.join("class/drm")) else {
+            return vec![];
+        };
+
+        let mut cards: Vec<IntelCard> = entries
+            .flatten()
+            .filter_map(|e| {
+                let name = e.file_name().to_string_lossy().to_string();
+                let number = name.strip_prefix("card")?.parse::<u32>().ok()?;
+                let device = e.path().join("device");
+
+                if read_string(device.join("vendor")).as_deref() != Some(INTEL_VENDOR_ID) {
+                    return None;
+                }
+
+                let uevent = read_string(device.join("uevent")).unwrap_or_default();
+                let uevent_value = |key: &str| {
+                    uevent
+                        .lines()
+                        .find_map(|l| l.strip_prefix(key)?.strip_prefix('='))
+                        .map(|v| v.to_string())
+                };
+
+                let driver = uevent_value("DRIVER")?;
+
+                match driver.as_str() {
+                    "i915" | "xe" => Some(IntelCard {
+                        name,
+                        number,
+                        path: e.path(),
+                        pdev: uevent_value("PCI_SLOT_NAME").unwrap_or_default(),
+                        driver,
+                    }),
+                    _ => None,
+                }
+            })
+            .collect();
+
+        cards.sort_by_key(|c| c.number);
+
+        cards
+    }
+
+    /// Collect the usage of every visible DRM client, grouped by the pci
+    /// address of the device they are using.
+    ///
+    /// A client may be reachable through several file descriptors, or even
+    /// several processes, so clients are deduplicated by their client id.
+    fn drm_clients(&self) -> HashMap<String, Vec<DrmClientUsage>> {
+        let mut clients = HashMap::<String, Vec<DrmClientUsage>>::new();
+
+        let Ok(processes) = fs::read_dir(&self.procfs_root) else {
+            return clients;
+        };
+
+        for process in processes.flatten() {
+            if !process
+                .file_name()
+                .to_string_lossy()
+                .chars()
+                .all(|c| c.is_ascii_digit())
+            {
+                continue;
+            }
+
+            let Ok(fds) = fs::read_dir(process.path().join("fd")) else {
+                continue;
+            };
+
+            for fd in fds.flatten() {
+                let is_drm =
+                    fs::read_link(fd.path()).is_ok_and(|target| target.starts_with("/dev/dri"));
+
+                if !is_drm {
+                    continue;
+                }
+
+                let fdinfo = process.path().join("fdinfo").join(fd.file_name());
+
+                let Some(usage) = fs::read_to_string(fdinfo)
+                    .ok()
+                    .and_then(|s| parse_fdinfo(&s))
+                else {
+                    continue;
+                };
+
+                let device_clients = clients.entry(usage.pdev.clone()).or_default();
+
+                if !device_clients
+                    .iter()
+                    .any(|c| c.client_id == usage.client_id)
+                {
+                    device_clients.push(usage);
+                }
+            }
+        }
+
+        clients
+    }
+}
+
+/// An Intel card found in sysfs.
+struct IntelCard {
+    name: String,
+    number: u32,
+    path: PathBuf,
+    pdev: String,
+    driver: String,
+}
+
+impl IntelCard {
+    /// Read the sysfs metrics of this card. Usage is computed separately.
+    fn poll(&self) -> GpuPollResult {
+        let device = self.path.join("device");
+
+        // i915 exposes frequencies on the card itself, xe exposes them per
+        // gt, of which the first is the primary graphics tile.
+        let graphics_clock = read_value::<u32>(self.path.join("gt_act_freq_mhz"))
+            .or_else(|| read_value(device.join("tile0/gt0/freq0/act_freq")));
+
+        let hwmon = find_hwmon(&device);
+        let hwmon_value = |file: &str| hwmon.as_ref().and_then(|h| read_value::<f32>(h.join(file)));
+
+        GpuPollResult {
+            name: format!(
+                "Intel GPU {} ({}, {})",
+                read_string(device.join("device")).unwrap_or_default(),
+                self.driver,
+                self.name
+            ),
+            // Integrated gpus do not report a temperature of their own, only
+            // discrete cards expose one through hwmon.
+            temp: hwmon_value("temp1_input").map_or(0f32, |t| t / 1000f32),
+            // hwmon reports power in microwatts.
+            power_usage: hwmon_value("power1_input").map(|p| p / 1_000_000f32),
+            fan_speed: None,
+            graphics_clock,
+            ..Default::default()
+        }
+    }
+}
+
+impl GpuBackend for IntelGpuBackend {
+    fn name(&self) -> &str {
+        "intel"
+    }
+
+    /// Find all Intel cards, and compute their utilization since the last
+    /// discovery, as usage is derived from all DRM clients at once.
+    fn discover(&mut self) -> Result<usize, GpuError> {
+        let cards = self.find_cards();
+
+        if cards.is_empty() {
+            self.cards = vec![];
+            return Ok(0);
+        }
+
+        let now = Instant::now();
+        let elapsed_ns = now.duration_since(self.last_time).as_nanos() as u64;
+        let clients = self.drm_clients();
+
+        self.cards = cards
+            .into_iter()
+            .map(|card| {
+                let current = clients.get(&card.pdev).cloned().unwrap_or_default();
+                let previous = self
+                    .last_clients
+                    .get(&card.pdev)
+                    .cloned()
+                    .unwrap_or_default();
+                let usage = engine_utilization(&previous, &current, elapsed_ns);
+
+                (card, usage)
+            })
+            .collect();
+
+        self.last_clients = clients;
+        self.last_time = now;
+
+        Ok(self.cards.len())
+    }
+
+    fn poll_device(&mut self, index: usize) -> Result<GpuPollResult, GpuError> {
+        match self.cards.get(index) {
+            Some((card, usage)) => Ok(GpuPollResult {
+                usage: *usage,
+                ..card.poll()
+            }),
+            None => Err(GpuError::new(self.name(), format!("no device {}", index))),
+        }
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+
+    static I915_FDINFO: &str = "pos:\t0
+flags:\t02100002
+mnt_id:\t26
+drm-driver:\ti915
+drm-pdev:\t0000:00:02.0
+drm-client-id:\t7
+drm-engine-render:\t25662044495 ns
+drm-engine-copy:\t0 ns
+drm-engine-video:\t1000000 ns
+drm-engine-capacity-video:\t2
+";
+
+    static XE_FDINFO: &str = "drm-driver:\txe
+drm-pdev:\t0000:03:00.0
+drm-client-id:\t12
+drm-cycles-rcs:\t28257900
+drm-total-cycles-rcs:\t7655183225
+";
+
+    fn fixture_root(dir: &str) -> String {
+        format!("{}/tests/fixtures/{}", env!("CARGO_MANIFEST_DIR"), dir)
+    }
+
+    #[test]
+    fn test_parse_i915_fdinfo() {
+        let usage = parse_fdinfo(I915_FDINFO).unwrap();
+
+        assert!(usage.driver == "i915");
+        assert!(usage.pdev == "0000:00:02.0");
+        assert!(usage.client_id == "7");
+        assert!(usage.engine_ns.get("render") == Some(&25662044495));
+        assert!(usage.engine_ns.get("video") == Some(&1000000));
+        assert!(!usage.engine_ns.contains_key("capacity-video"));
+    }
+
+    #[test]
+    fn test_parse_xe_fdinfo() {
+        let usage = parse_fdinfo(XE_FDINFO).unwrap();
+
+        assert!(usage.engine_cycles.get("rcs") == Some(&(28257900, 7655183225)));
+    }
+
+    #[test]
+    fn test_parse_non_drm_fdinfo() {
+        assert!(parse_fdinfo("pos:\t0\nflags:\t02\n").is_none());
+    }
+
+    #[test]
+    fn test_engine_utilization() {
+        let previous = parse_fdinfo(I915_FDINFO).unwrap();
+        let mut current = previous.clone();
+
+        // 250ms of render time over a 1s interval.
+        *current.engine_ns.get_mut("render").unwrap() += 250_000_000;
+
+        let usage = engine_utilization(&[previous], &[current], 1_000_000_000);
+        assert!((usage - 25.0).abs() < 0.01);
+    }
+
+    #[test]
+    fn test_engine_utilization_new_client() {
+        let current = parse_fdinfo(I915_FDINFO).unwrap();
+
+        assert!(engine_utilization(&[], &[current], 1_000_000_000) == 0.0);
+    }
+
+    #[test]
+    fn test_poll_fixture() {
+        let mut backend = IntelGpuBackend::with_roots(fixture_root("sys"), fixture_root("proc"));
+
+        assert!(backend.discover() == Ok(1));
+
+        let gpu = backend.poll_device(0).unwrap();
+        assert!(gpu.name == "Intel GPU 0x9a49 (i915, card1)");
+        assert!(gpu.graphics_clock == Some(1300));
+        assert!(backend.poll_device(1).is_err());
+
+        let clients = backend.drm_clients();
+        assert!(clients.get("0000:00:02.0").map(|c| c.len()) == Some(1));
+    }
+}
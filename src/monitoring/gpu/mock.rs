@@ -0,0 +1,73 @@
+/// Module mock contains a [`GpuBackend`] serving canned readings, which
+/// allows gpu polling and rendering to be tested on machines without a gpu.
+use crate::monitoring::polling::GpuPollResult;
+
+use super::{GpuBackend, GpuError};
+
+/// [`MockGpuBackend`] reports a fixed list of devices.
+///
+/// # Example
+/// ```
+/// use mainframe::monitoring::gpu::{mock::MockGpuBackend, poll_backends, GpuBackend};
+/// use mainframe::monitoring::polling::GpuPollResult;
+///
+/// let mut backends: Vec<Box<dyn GpuBackend>> = vec![Box::new(MockGpuBackend::new(vec![
+///     GpuPollResult::default(),
+/// ]))];
+///
+/// let (gpus, errors) = poll_backends(&mut backends);
+/// assert!(gpus.len() == 1 && errors.is_empty());
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct MockGpuBackend {
+    devices: Vec<Result<GpuPollResult, String>>,
+    discover_error: Option<String>,
+}
+
+impl MockGpuBackend {
+    /// Create a backend reporting the given devices.
+    pub fn new(devices: Vec<GpuPollResult>) -> Self {
+        MockGpuBackend {
+            devices: devices.into_iter().map(Ok).collect(),
+            discover_error: None,
+        }
+    }
+
+    /// Create a backend whose device discovery always fails.
+    pub fn failing(message: impl Into<String>) -> Self {
+        MockGpuBackend {
+            devices: vec![],
+            discover_error: Some(message.into()),
+        }
+    }
+
+    /// Make polls of the device at `index` fail with the given message.
+    pub fn with_device_error(mut self, index: usize, message: impl Into<String>) -> Self {
+        if let Some(device) = self.devices.get_mut(index) {
+            *device = Err(message.into());
+        }
+
+        self
+    }
+}
+
+impl GpuBackend for MockGpuBackend {
+    fn name(&self) -> &str {
+        "mock"
+    }
+
+    fn discover(&mut self) -> Result<usize, GpuError> {
+        match &self.discover_error {
+            Some(message) => Err(GpuError::new(self.name(), message)),
+            None => Ok(self.devices.len()),
+        }
+    }
+
+    fn poll_device(&mut self, index: usize) -> Result<GpuPollResult, GpuError> {
+        match self.devices.get(index) {
+            Some(Ok(gpu)) => Ok(gpu.clone()),
+            Some(Err(message)) => Err(GpuError::new(self.name(), message)),
+            None => Err(GpuError::new(self.name(), format!("no device {}", index))),
+        }
+    }
+}
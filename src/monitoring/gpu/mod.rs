@@ -0,0 +1,253 @@
+/// Module gpu contains the [`GpuBackend`] trait, and its implementations for
+/// each supported gpu vendor.
+///
+/// Several backends may be active at once, for example on a laptop with an
+/// integrated Intel gpu, and a discrete Nvidia gpu.
+use std::fmt;
+
+use super::polling::GpuPollResult;
+
+pub mod amd;
+pub mod intel;
+pub mod mock;
+pub mod nvml;
+
+/// [`GpuError`] describes a failure of a [`GpuBackend`], either while
+/// discovering devices, or while polling a single device.
+#[derive(Clone, Debug, PartialEq)]
+pub struct GpuError {
+    /// Name of the backend which reported the error.
+    pub backend: String,
+    pub message: String,
+}
+
+impl GpuError {
+    /// Create an error reported by the backend named `backend`.
+    pub fn new(backend: impl Into<String>, message: impl ToString) -> Self {
+        GpuError {
+            backend: backend.into(),
+            message: message.to_string(),
+        }
+    }
+}
+
+impl fmt::Display for GpuError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.backend, self.message)
+    }
+}
+
+impl std::error::Error for GpuError {}
+
+/// [`GpuHealth`] contains diagnostics which tell a throttled, or failing
+/// device apart from a busy one.
+///
+/// Optional fields are `None` when the device does not support the
+/// corresponding query, such as ECC counts on consumer cards.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct GpuHealth {
+    /// Current performance state, from 0 (maximum performance) to 15
+    /// (minimum performance).
+    pub performance_state: Option<u32>,
+    /// Reasons the device's clocks are currently being held down.
+    pub throttle_reasons: Vec<ThrottleReason>,
+    /// Corrected memory errors since the driver was loaded.
+    pub ecc_corrected: Option<u64>,
+    /// Uncorrected memory errors since the driver was loaded.
+    pub ecc_uncorrected: Option<u64>,
+    /// Number of memory pages retired due to ECC errors.
+    pub retired_pages: Option<u32>,
+    /// Whether pages are waiting to be retired on the next reboot.
+    pub retired_pages_pending: Option<bool>,
+}
+
+impl GpuHealth {
+    /// Short warning badges summarizing any problems with the device. A
+    /// healthy device, or a device which is only idle, has none.
+    pub fn warnings(&self) -> Vec<&'static str> {
+        let mut warnings = vec![];
+        let throttled =
+            |reasons: &[ThrottleReason]| reasons.iter().any(|r| self.throttle_reasons.contains(r));
+
+        if throttled(&[ThrottleReason::SwThermal, ThrottleReason::HwThermal]) {
+            warnings.push("THERMAL");
+        }
+
+        if throttled(&[ThrottleReason::SwPowerCap, ThrottleReason::HwPowerBrake]) {
+            warnings.push("POWER");
+        }
+
+        if throttled(&[ThrottleReason::HwSlowdown]) {
+            warnings.push("HW SLOWDOWN");
+        }
+
+        if self.ecc_uncorrected.is_some_and(|e| e > 0) {
+            warnings.push("ECC");
+        }
+
+        if self.retired_pages_pending == Some(true) {
+            warnings.push("PAGES PENDING");
+        }
+
+        warnings
+    }
+}
+
+/// [`ThrottleReason`] enumerates the reasons a gpu may run below its
+/// maximum clocks.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ThrottleReason {
+    Idle,
+    ApplicationClocks,
+    SwPowerCap,
+    HwSlowdown,
+    SyncBoost,
+    SwThermal,
+    HwThermal,
+    HwPowerBrake,
+    DisplayClocks,
+}
+
+impl ThrottleReason {
+    /// Human readable description of this throttle reason.
+    pub fn description(&self) -> &'static str {
+        match self {
+            Self::Idle => "Idle",
+            Self::ApplicationClocks => "Application clock setting",
+            Self::SwPowerCap => "Software power cap",
+            Self::HwSlowdown => "Hardware slowdown",
+            Self::SyncBoost => "Sync boost",
+            Self::SwThermal => "Software thermal slowdown",
+            Self::HwThermal => "Hardware thermal slowdown",
+            Self::HwPowerBrake => "Hardware power brake",
+            Self::DisplayClocks => "Display clock setting",
+        }
+    }
+}
+
+/// [`GpuBackend`] is implemented by every source of gpu metrics.
+///
+/// On each poll, [`GpuBackend::discover()`] is called once, after which every
+/// discovered device is polled with [`GpuBackend::poll_device()`]. An error
+/// polling one device does not prevent the others from being polled.
+///
+/// Backends are moved into the polling task, and so must be [`Send`].
+pub trait GpuBackend: Send {
+    /// Short, human readable name of this backend, such as `"nvml"`.
+    fn name(&self) -> &str;
+
+    /// Discover the devices currently available to this backend, and return
+    /// how many there are.
+    fn discover(&mut self) -> Result<usize, GpuError>;
+
+    /// Obtain a [`GpuPollResult`] reading for the device at `index`, where
+    /// `index` is less than the count returned by the last call to
+    /// [`GpuBackend::discover()`].
+    fn poll_device(&mut self, index: usize) -> Result<GpuPollResult, GpuError>;
+}
+
+/// Create a backend for every gpu vendor supported on this system.
+///
+/// The nvml backend is only created if the nvidia management library could
+/// be loaded. The sysfs based backends are always created, and simply report
+/// no devices when none are present.
+pub fn default_backends() -> Vec<Box<dyn GpuBackend>> {
+    let mut backends: Vec<Box<dyn GpuBackend>> = vec![];
+
+    if let Some(nvml) = nvml::NvmlBackend::init() {
+        backends.push(Box::new(nvml));
+    }
+
+    backends.push(Box::new(amd::AmdGpuBackend::new()));
+    backends.push(Box::new(intel::IntelGpuBackend::new()));
+
+    backends
+}
+
+/// Poll every device of every backend, in order.
+///
+/// Returns all successful readings, and the errors of any failed discovery
+/// or device poll.
+pub fn poll_backends(backends: &mut [Box<dyn GpuBackend>]) -> (Vec<GpuPollResult>, Vec<GpuError>) {
+    let mut gpus = vec![];
+    let mut errors = vec![];
+
+    for backend in backends.iter_mut() {
+        let count = match backend.discover() {
+            Ok(count) => count,
+            Err(e) => {
+                errors.push(e);
+                continue;
+            }
+        };
+
+        for i in 0..count {
+            match backend.poll_device(i) {
+                Ok(gpu) => gpus.push(gpu),
+                Err(e) => errors.push(e),
+            }
+        }
+    }
+
+    (gpus, errors)
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::mock::MockGpuBackend;
+    use super::*;
+
+    fn gpu(name: &str) -> GpuPollResult {
+        GpuPollResult {
+            name: name.to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_gpu_health_warnings() {
+        let mut health = GpuHealth {
+            throttle_reasons: vec![ThrottleReason::Idle],
+            ecc_uncorrected: Some(0),
+            retired_pages_pending: Some(false),
+            ..Default::default()
+        };
+
+        // An idle gpu is not a sick gpu.
+        assert!(health.warnings().is_empty());
+
+        health.throttle_reasons = vec![ThrottleReason::SwPowerCap, ThrottleReason::HwThermal];
+        health.ecc_uncorrected = Some(3);
+        health.retired_pages_pending = Some(true);
+
+        assert!(health.warnings() == ["THERMAL", "POWER", "ECC", "PAGES PENDING"]);
+    }
+
+    #[test]
+    fn test_poll_multiple_backends() {
+        let mut backends: Vec<Box<dyn GpuBackend>> = vec![
+            Box::new(MockGpuBackend::new(vec![gpu("a"), gpu("b")])),
+            Box::new(MockGpuBackend::new(vec![gpu("c")])),
+        ];
+
+        let (gpus, errors) = poll_backends(&mut backends);
+
+        assert!(gpus.iter().map(|g| g.name.as_str()).collect::<Vec<_>>() == ["a", "b", "c"]);
+        assert!(errors.is_empty());
+    }
+
+    #[test]
+    fn test_poll_backend_errors() {
+        let mut backends: Vec<Box<dyn GpuBackend>> = vec![
+            Box::new(MockGpuBackend::failing("driver not loaded")),
+            Box::new(MockGpuBackend::new(vec![gpu("a"), gpu("b")]).with_device_error(0, "gone")),
+        ];
+
+        let (gpus, errors) = poll_backends(&mut backends);
+
+        assert!(gpus.len() == 1 && gpus[0].name == "b");
+        assert!(errors.len() == 2);
+        assert!(errors[0].to_string() == "mock: driver not loaded");
+        assert!(errors[1].to_string() == "mock: gone");
+    }
+}
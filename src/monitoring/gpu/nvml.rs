@@ -0,0 +1,305 @@
+/// Module nvml contains functionality to poll Nvidia gpus through the nvidia
+/// management library.
+use std::collections::HashMap;
+
+use nvml_wrapper::{
+    bitmasks::device::ThrottleReasons,
+    enum_wrappers::device::{
+        Clock, EccCounter, MemoryError, PcieUtilCounter, PerformanceState, RetirementCause,
+        TemperatureSensor,
+    },
+    enums::device::UsedGpuMemory,
+    struct_wrappers::device::{ProcessInfo, ProcessUtilizationSample},
+    Device, Nvml,
+};
+
+use crate::monitoring::polling::{GpuPollResult, GpuProcess};
+
+use super::{GpuBackend, GpuError, GpuHealth, ThrottleReason};
+
+/// [`NvmlBackend`] reads gpu metrics for every device managed by the Nvidia
+/// driver.
+///
+/// Metrics which a device does not support are left at their default value,
+/// while failing to find the device itself is reported as a [`GpuError`].
+pub struct NvmlBackend {
+    nvml: Nvml,
+    /// Timestamp of the newest process utilization sample seen, per device.
+    last_sample_time: HashMap<usize, u64>,
+}
+
+impl NvmlBackend {
+    /// Load the nvidia management library.
+    ///
+    /// Returns `None` when the library is not installed, or no Nvidia driver
+    /// is loaded.
+    pub fn init() -> Option<Self> {
+        Nvml::init().ok().map(|nvml| NvmlBackend {
+            nvml,
+            last_sample_time: HashMap::new(),
+        })
+    }
+}
+
+/// Combine the running compute and graphics processes of a device with its
+/// process utilization samples, into one [`GpuProcess`] per pid, ordered by
+/// memory usage.
+///
+/// A process may be listed as both a compute and a graphics process, and
+/// may have several utilization samples, of which the newest is used.
+fn gpu_processes(
+    running: &[ProcessInfo],
+    samples: &[ProcessUtilizationSample],
+    process_name: impl Fn(u32) -> String,
+) -> Vec<GpuProcess> {
+    let mut processes = HashMap::<u32, GpuProcess>::new();
+
+    for info in running {
+        let process = processes.entry(info.pid).or_insert_with(|| GpuProcess {
+            pid: info.pid,
+            name: process_name(info.pid),
+            ..Default::default()
+        });
+
+        if let UsedGpuMemory::Used(bytes) = info.used_gpu_memory {
+            process.memory_used = Some(process.memory_used.unwrap_or(0).max(bytes));
+        }
+    }
+
+    let mut newest = HashMap::<u32, &ProcessUtilizationSample>::new();
+
+    for sample in samples {
+        let entry = newest.entry(sample.pid).or_insert(sample);
+
+        if sample.timestamp > entry.timestamp {
+            *entry = sample;
+        }
+    }
+
+    for (pid, sample) in newest {
+        // Samples may outlive the process which produced them.
+        if let Some(process) = processes.get_mut(&pid) {
+            process.sm_usage = Some(sample.sm_util);
+        }
+    }
+
+    let mut processes: Vec<GpuProcess> = processes.into_values().collect();
+    processes.sort_by(|a, b| b.memory_used.cmp(&a.memory_used).then(a.pid.cmp(&b.pid)));
+
+    processes
+}
+
+/// Translate an nvml throttle reason bitmask.
+fn throttle_reasons(reasons: ThrottleReasons) -> Vec<ThrottleReason> {
+    [
+        (ThrottleReasons::GPU_IDLE, ThrottleReason::Idle),
+        (
+            ThrottleReasons::APPLICATIONS_CLOCKS_SETTING,
+            ThrottleReason::ApplicationClocks,
+        ),
+        (ThrottleReasons::SW_POWER_CAP, ThrottleReason::SwPowerCap),
+        (ThrottleReasons::HW_SLOWDOWN, ThrottleReason::HwSlowdown),
+        (ThrottleReasons::SYNC_BOOST, ThrottleReason::SyncBoost),
+        (
+            ThrottleReasons::SW_THERMAL_SLOWDOWN,
+            ThrottleReason::SwThermal,
+        ),
+        (
+            ThrottleReasons::HW_THERMAL_SLOWDOWN,
+            ThrottleReason::HwThermal,
+        ),
+        (
+            ThrottleReasons::HW_POWER_BRAKE_SLOWDOWN,
+            ThrottleReason::HwPowerBrake,
+        ),
+        (
+            ThrottleReasons::DISPLAY_CLOCK_SETTING,
+            ThrottleReason::DisplayClocks,
+        ),
+    ]
+    .into_iter()
+    .filter(|(flag, _)| reasons.contains(*flag))
+    .map(|(_, reason)| reason)
+    .collect()
+}
+
+/// Read the health diagnostics of a device.
+fn device_health(device: &Device) -> GpuHealth {
+    let ecc = |error_type| {
+        device
+            .total_ecc_errors(error_type, EccCounter::Volatile)
+            .ok()
+    };
+
+    let retired_pages = [
+        RetirementCause::MultipleSingleBitEccErrors,
+        RetirementCause::DoubleBitEccError,
+    ]
+    .into_iter()
+    .map(|cause| device.retired_pages(cause).map(|pages| pages.len() as u32))
+    .collect::<Result<Vec<u32>, _>>()
+    .ok()
+    .map(|counts| counts.iter().sum());
+
+    GpuHealth {
+        performance_state: device.performance_state().ok().and_then(|p| match p {
+            PerformanceState::Unknown => None,
+            p => Some(p.as_c()),
+        }),
+        throttle_reasons: device
+            .current_throttle_reasons()
+            .map(throttle_reasons)
+            .unwrap_or_default(),
+        ecc_corrected: ecc(MemoryError::Corrected),
+        ecc_uncorrected: ecc(MemoryError::Uncorrected),
+        retired_pages,
+        retired_pages_pending: device.are_pages_pending_retired().ok(),
+    }
+}
+
+impl GpuBackend for NvmlBackend {
+    fn name(&self) -> &str {
+        "nvml"
+    }
+
+    fn discover(&mut self) -> Result<usize, GpuError> {
+        self.nvml
+            .device_count()
+            .map(|count| count as usize)
+            .map_err(|e| GpuError::new(self.name(), e))
+    }
+
+    fn poll_device(&mut self, index: usize) -> Result<GpuPollResult, GpuError> {
+        let device = self
+            .nvml
+            .device_by_index(index as u32)
+            .map_err(|e| GpuError::new(self.name(), format!("device {}: {}", index, e)))?;
+
+        let memory_info = device.memory_info();
+
+        let mut running = device.running_compute_processes().unwrap_or_default();
+        running.extend(device.running_graphics_processes().unwrap_or_default());
+
+        // Only samples newer than the previous poll are requested, so that
+        // processes which have gone idle stop reporting usage.
+        let samples = device
+            .process_utilization_stats(self.last_sample_time.get(&index).copied())
+            .unwrap_or_default();
+
+        if let Some(newest) = samples.iter().map(|s| s.timestamp).max() {
+            self.last_sample_time.insert(index, newest);
+        }
+
+        let nvml = &self.nvml;
+        let processes = gpu_processes(&running, &samples, |pid| {
+            nvml.sys_process_name(pid, 64)
+                .unwrap_or_else(|_| "???".to_string())
+        });
+
+        Ok(GpuPollResult {
+            name: match device.name() {
+                Ok(n) => n,
+                Err(e) => e.to_string(),
+            },
+            temp: match device.temperature(TemperatureSensor::Gpu) {
+                Ok(t) => t as f32,
+                Err(_) => 0f32,
+            },
+            usage: match device.utilization_rates() {
+                Ok(r) => r.gpu as f32,
+                Err(_) => 0f32,
+            },
+            memory_total: match &memory_info {
+                Ok(m) => m.total,
+                Err(_) => 0,
+            },
+            memory_used: match memory_info {
+                Ok(m) => m.used,
+                Err(_) => 0,
+            },
+            // nvml reports power in milliwatts.
+            power_usage: device.power_usage().ok().map(|p| p as f32 / 1000f32),
+            power_limit: device
+                .enforced_power_limit()
+                .ok()
+                .map(|p| p as f32 / 1000f32),
+            fan_speed: device.fan_speed(0).ok(),
+            graphics_clock: device.clock_info(Clock::SM).ok(),
+            memory_clock: device.clock_info(Clock::Memory).ok(),
+            // nvml reports pcie throughput in KB/s.
+            pcie_tx: device
+                .pcie_throughput(PcieUtilCounter::Send)
+                .ok()
+                .map(|t| t as u64 * 1024),
+            pcie_rx: device
+                .pcie_throughput(PcieUtilCounter::Receive)
+                .ok()
+                .map(|t| t as u64 * 1024),
+            encoder_usage: device.encoder_utilization().ok().map(|u| u.utilization),
+            decoder_usage: device.decoder_utilization().ok().map(|u| u.utilization),
+            processes,
+            health: device_health(&device),
+        })
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+
+    fn info(pid: u32, memory: Option<u64>) -> ProcessInfo {
+        ProcessInfo {
+            pid,
+            used_gpu_memory: memory.map_or(UsedGpuMemory::Unavailable, UsedGpuMemory::Used),
+            gpu_instance_id: None,
+            compute_instance_id: None,
+        }
+    }
+
+    fn sample(pid: u32, timestamp: u64, sm_util: u32) -> ProcessUtilizationSample {
+        ProcessUtilizationSample {
+            pid,
+            timestamp,
+            sm_util,
+            mem_util: 0,
+            enc_util: 0,
+            dec_util: 0,
+        }
+    }
+
+    #[test]
+    fn test_throttle_reasons() {
+        let reasons =
+            throttle_reasons(ThrottleReasons::SW_POWER_CAP | ThrottleReasons::HW_THERMAL_SLOWDOWN);
+
+        assert!(reasons == [ThrottleReason::SwPowerCap, ThrottleReason::HwThermal]);
+        assert!(throttle_reasons(ThrottleReasons::NONE).is_empty());
+    }
+
+    #[test]
+    fn test_gpu_processes() {
+        let running = [
+            info(10, Some(100)),
+            info(20, Some(300)),
+            // Listed again as a graphics process.
+            info(10, Some(100)),
+            info(30, None),
+        ];
+        let samples = [
+            sample(10, 1, 5),
+            sample(10, 2, 40),
+            sample(20, 1, 15),
+            // Process which has since exited.
+            sample(99, 2, 80),
+        ];
+
+        let processes = gpu_processes(&running, &samples, |pid| format!("p{}", pid));
+
+        assert!(processes.iter().map(|p| p.pid).collect::<Vec<_>>() == [20, 10, 30]);
+        assert!(processes[0].name == "p20");
+        assert!(processes[1].memory_used == Some(100));
+        assert!(processes[1].sm_usage == Some(40));
+        assert!(processes[2].memory_used.is_none());
+        assert!(processes[2].sm_usage.is_none());
+    }
+}
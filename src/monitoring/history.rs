@@ -0,0 +1,109 @@
+/// Module history contains functionality to keep the latest poll result,
+/// along with a history of the series which are drawn as graphs.
+use crate::ringbuffer::RingBuffer;
+
+use super::{
+    network::NetworkPollResult,
+    polling::{GpuPollResult, Measurement, SystemPollResult},
+};
+
+/// [`PollHistory`] holds the latest [`SystemPollResult`] in full, and a
+/// fixed number of past readings of cpu usage, network interfaces, and gpus.
+///
+/// Past readings only keep what the graphs draw, so gpu processes, and
+/// interface addresses are left out of them.
+pub struct PollHistory {
+    latest: SystemPollResult,
+    cpu_usage: RingBuffer<Vec<Measurement>>,
+    network: RingBuffer<Vec<NetworkPollResult>>,
+    gpu_info: RingBuffer<Vec<GpuPollResult>>,
+}
+
+impl PollHistory {
+    /// Create an empty history, which keeps the last `length` readings.
+    pub fn new(length: usize) -> Self {
+        PollHistory {
+            latest: SystemPollResult::default(),
+            cpu_usage: RingBuffer::new(length),
+            network: RingBuffer::new(length),
+            gpu_info: RingBuffer::new(length),
+        }
+    }
+
+    /// Add a poll result, which becomes the latest one.
+    pub fn add(&mut self, res: SystemPollResult) {
+        self.cpu_usage.add(res.cpu_usage.clone());
+        self.network.add(
+            res.network
+                .iter()
+                .map(|i| NetworkPollResult {
+                    addresses: vec![],
+                    ..i.clone()
+                })
+                .collect(),
+        );
+        self.gpu_info.add(
+            res.gpu_info
+                .iter()
+                .map(|g| GpuPollResult {
+                    processes: vec![],
+                    ..g.clone()
+                })
+                .collect(),
+        );
+
+        self.latest = res;
+    }
+
+    /// The most recent poll result, or an empty one before the first poll.
+    pub fn latest(&self) -> &SystemPollResult {
+        &self.latest
+    }
+
+    /// Cpu usage readings, from oldest to newest.
+    pub fn cpu_usage(&self) -> impl Iterator<Item = &[Measurement]> {
+        self.cpu_usage.iter().map(Vec::as_slice)
+    }
+
+    /// Network interface readings, from oldest to newest.
+    pub fn network(&self) -> impl Iterator<Item = &[NetworkPollResult]> {
+        self.network.iter().map(Vec::as_slice)
+    }
+
+    /// Gpu readings, from oldest to newest.
+    pub fn gpu_info(&self) -> impl Iterator<Item = &[GpuPollResult]> {
+        self.gpu_info.iter().map(Vec::as_slice)
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+    use crate::monitoring::polling::GpuProcess;
+
+    #[test]
+    fn test_poll_history() {
+        let mut history = PollHistory::new(2);
+
+        assert!(history.latest().gpu_info.is_empty());
+        assert!(history.gpu_info().count() == 0);
+
+        for usage in [10f32, 20f32, 30f32] {
+            history.add(SystemPollResult {
+                gpu_info: vec![GpuPollResult {
+                    usage,
+                    processes: vec![GpuProcess::default()],
+                    ..Default::default()
+                }],
+                ..Default::default()
+            });
+        }
+
+        let usages: Vec<f32> = history.gpu_info().map(|g| g[0].usage).collect();
+        assert!(usages == [20f32, 30f32]);
+
+        // Only the latest reading keeps its processes.
+        assert!(history.gpu_info().all(|g| g[0].processes.is_empty()));
+        assert!(history.latest().gpu_info[0].processes.len() == 1);
+    }
+}
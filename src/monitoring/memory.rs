@@ -0,0 +1,219 @@
+/// Module memory contains functionality to poll detailed memory, and swap
+/// usage from `/proc/meminfo` and `/proc/vmstat`.
+use std::{collections::HashMap, fs, path::PathBuf, time::Instant};
+
+/// [`MemoryPollResult`] contains memory, and swap usage obtained by polling.
+///
+/// All sizes are in bytes.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct MemoryPollResult {
+    pub total: u64,
+    pub free: u64,
+    /// Estimate of memory available to start new applications without
+    /// swapping, including reclaimable cache.
+    pub available: u64,
+    pub buffers: u64,
+    /// Page cache, including shared memory.
+    pub cached: u64,
+    /// Shared memory, such as tmpfs files.
+    pub shared: u64,
+    pub dirty: u64,
+    pub writeback: u64,
+    pub slab: u64,
+    /// Part of [`Self::slab`] which can be reclaimed under pressure.
+    pub slab_reclaimable: u64,
+    pub hugepages_total: u64,
+    pub hugepages_free: u64,
+    pub hugepage_size: u64,
+    pub swap_total: u64,
+    pub swap_free: u64,
+    /// Rate at which memory is swapped in, in bytes per second. Always 0 on
+    /// the first poll.
+    pub swap_in_per_sec: f64,
+    /// Rate at which memory is swapped out, in bytes per second. Always 0 on
+    /// the first poll.
+    pub swap_out_per_sec: f64,
+}
+
+impl MemoryPollResult {
+    /// Memory used by applications and the kernel, which cannot simply be
+    /// dropped. This excludes free memory, buffers, and reclaimable caches.
+    pub fn used(&self) -> u64 {
+        self.total
+            .saturating_sub(self.free)
+            .saturating_sub(self.buffers)
+            .saturating_sub(self.cache())
+    }
+
+    /// Reclaimable cache, made up of the page cache, and reclaimable slab.
+    pub fn cache(&self) -> u64 {
+        self.cached + self.slab_reclaimable
+    }
+
+    pub fn swap_used(&self) -> u64 {
+        self.swap_total.saturating_sub(self.swap_free)
+    }
+}
+
+/// Parse the contents of `/proc/meminfo` into values by key.
+///
+/// Values given in kB are converted to bytes, while unitless values, such as
+/// page counts, are kept as is.
+pub fn parse_meminfo(contents: &str) -> HashMap<&str, u64> {
+    contents
+        .lines()
+        .filter_map(|line| {
+            let (key, value) = line.split_once(':')?;
+            let mut parts = value.split_whitespace();
+            let value = parts.next()?.parse::<u64>().ok()?;
+
+            match parts.next() {
+                Some("kB") => Some((key, value * 1024)),
+                _ => Some((key, value)),
+            }
+        })
+        .collect()
+}
+
+/// Parse the contents of `/proc/vmstat` into counters by name.
+pub fn parse_vmstat(contents: &str) -> HashMap<&str, u64> {
+    contents
+        .lines()
+        .filter_map(|line| {
+            let (key, value) = line.split_once(' ')?;
+
+            Some((key, value.trim().parse().ok()?))
+        })
+        .collect()
+}
+
+/// [`MemoryPoller`] reads memory usage from `<procfs root>/meminfo`, and
+/// swap activity from `<procfs root>/vmstat`.
+pub struct MemoryPoller {
+    procfs_root: PathBuf,
+    page_size: u64,
+    /// Pages swapped in and out as of the last poll.
+    last_swap_pages: Option<(u64, u64)>,
+    last_time: Instant,
+}
+
+impl Default for MemoryPoller {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl MemoryPoller {
+    /// Create a new poller reading from `/proc`.
+    pub fn new() -> Self {
+        Self::with_procfs_root("/proc")
+    }
+
+    /// Create a new poller reading from the given procfs root.
+    pub fn with_procfs_root(root: impl Into<PathBuf>) -> Self {
+        // SAFETY: sysconf has no preconditions.
+        let page_size = unsafe { libc::sysconf(libc::_SC_PAGESIZE) };
+
+        MemoryPoller {
+            procfs_root: root.into(),
+            page_size: u64::try_from(page_size).unwrap_or(4096),
+            last_swap_pages: None,
+            last_time: Instant::now(),
+        }
+    }
+
+    /// Obtain a [`MemoryPollResult`] reading.
+    ///
+    /// Values which could not be read are left at 0.
+    pub fn poll(&mut self) -> MemoryPollResult {
+        let meminfo = fs::read_to_string(self.procfs_root.join("meminfo")).unwrap_or_default();
+        let vmstat = fs::read_to_string(self.procfs_root.join("vmstat")).unwrap_or_default();
+
+        let meminfo = parse_meminfo(&meminfo);
+        let vmstat = parse_vmstat(&vmstat);
+        let value = |key: &str| meminfo.get(key).copied().unwrap_or(0);
+
+        let now = Instant::now();
+        let seconds = now
+            .duration_since(self.last_time)
+            .as_secs_f64()
+            .max(f64::EPSILON);
+
+        let swap_pages = (
+            vmstat.get("pswpin").copied().unwrap_or(0),
+            vmstat.get("pswpout").copied().unwrap_or(0),
+        );
+
+        let rate = |current: u64, previous: u64| {
+            (current.saturating_sub(previous) * self.page_size) as f64 / seconds
+        };
+
+        let (swap_in_per_sec, swap_out_per_sec) = match self.last_swap_pages {
+            Some((last_in, last_out)) => {
+                (rate(swap_pages.0, last_in), rate(swap_pages.1, last_out))
+            }
+            None => (0f64, 0f64),
+        };
+
+        self.last_swap_pages = Some(swap_pages);
+        self.last_time = now;
+
+        MemoryPollResult {
+            total: value("MemTotal"),
+            free: value("MemFree"),
+            available: value("MemAvailable"),
+            buffers: value("Buffers"),
+            cached: value("Cached"),
+            shared: value("Shmem"),
+            dirty: value("Dirty"),
+            writeback: value("Writeback"),
+            slab: value("Slab"),
+            slab_reclaimable: value("SReclaimable"),
+            hugepages_total: value("HugePages_Total"),
+            hugepages_free: value("HugePages_Free"),
+            hugepage_size: value("Hugepagesize"),
+            swap_total: value("SwapTotal"),
+            swap_free: value("SwapFree"),
+            swap_in_per_sec,
+            swap_out_per_sec,
+        }
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+
+    fn fixture_root() -> String {
+        format!("{}/tests/fixtures/proc", env!("CARGO_MANIFEST_DIR"))
+    }
+
+    #[test]
+    fn test_parse_meminfo() {
+        let meminfo = parse_meminfo("MemTotal:  1000 kB\nHugePages_Total:  16\nbogus\n");
+
+        assert!(meminfo["MemTotal"] == 1024000);
+        assert!(meminfo["HugePages_Total"] == 16);
+        assert!(meminfo.len() == 2);
+    }
+
+    #[test]
+    fn test_poll_fixture() {
+        let mut poller = MemoryPoller::with_procfs_root(fixture_root());
+
+        let memory = poller.poll();
+
+        assert!(memory.total == 16384000 * 1024);
+        assert!(memory.available == 8192000 * 1024);
+        assert!(memory.shared == 256000 * 1024);
+        assert!(memory.hugepages_total == 16);
+        assert!(memory.hugepage_size == 2048 * 1024);
+        assert!(memory.swap_used() == 2048000 * 1024);
+        assert!(memory.cache() == (4096000 + 409600) * 1024);
+        assert!(memory.used() == (16384000 - 2048000 - 512000 - 4096000 - 409600) * 1024);
+
+        // Swap rates need two polls, and the counters have not changed.
+        assert!(memory.swap_in_per_sec == 0.0);
+        assert!(poller.poll().swap_out_per_sec == 0.0);
+    }
+}
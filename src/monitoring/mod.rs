@@ -1,2 +1,21 @@
+pub mod actions;
+pub mod battery;
+pub mod cgroup;
+pub mod cgroup_tree;
+pub mod cpufreq;
+pub mod diskio;
+pub mod energy;
+pub mod filesystem;
+pub mod gpu;
+pub mod history;
+pub mod memory;
+pub mod network;
+pub mod owner;
 pub mod polling;
+pub mod pressure;
+pub mod process;
+pub mod procstat;
+pub mod rapl;
+pub mod sensors;
+pub mod sysfs;
 pub mod system;
@@ -0,0 +1,291 @@
+/// Module network contains functionality to poll network interfaces through
+/// `/sys/class/net`.
+use std::{
+    collections::HashMap,
+    ffi::CStr,
+    fs,
+    net::{IpAddr, Ipv4Addr, Ipv6Addr},
+    path::PathBuf,
+    time::Instant,
+};
+
+use super::sysfs::{read_string, read_value};
+
+/// ARPHRD_LOOPBACK, the `type` of loopback interfaces.
+static LOOPBACK_TYPE: u32 = 772;
+
+/// [`InterfaceCounters`] holds the traffic counters of a network interface.
+///
+/// Counters are read as `u64` totals since the interface was created, and
+/// rates between two polls are expressed as `f64` per second values.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct InterfaceCounters<T> {
+    pub rx_bytes: T,
+    pub tx_bytes: T,
+    pub rx_packets: T,
+    pub tx_packets: T,
+    pub rx_errors: T,
+    pub tx_errors: T,
+    pub rx_dropped: T,
+    pub tx_dropped: T,
+}
+
+impl InterfaceCounters<u64> {
+    /// Per second rates of each counter, between the `previous` counters and
+    /// these, read `seconds` apart.
+    ///
+    /// Counters which went backwards, as happens when an interface is
+    /// recreated, report a rate of 0.
+    pub fn rates_since(&self, previous: &Self, seconds: f64) -> InterfaceCounters<f64> {
+        let rate =
+            |cur: u64, prev: u64| cur.saturating_sub(prev) as f64 / seconds.max(f64::EPSILON);
+
+        InterfaceCounters {
+            rx_bytes: rate(self.rx_bytes, previous.rx_bytes),
+            tx_bytes: rate(self.tx_bytes, previous.tx_bytes),
+            rx_packets: rate(self.rx_packets, previous.rx_packets),
+            tx_packets: rate(self.tx_packets, previous.tx_packets),
+            rx_errors: rate(self.rx_errors, previous.rx_errors),
+            tx_errors: rate(self.tx_errors, previous.tx_errors),
+            rx_dropped: rate(self.rx_dropped, previous.rx_dropped),
+            tx_dropped: rate(self.tx_dropped, previous.tx_dropped),
+        }
+    }
+}
+
+/// [`NetworkPollResult`] contains network interface data obtained by polling.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct NetworkPollResult {
+    pub name: String,
+    /// Counter totals since the interface was created.
+    pub totals: InterfaceCounters<u64>,
+    /// Per second rates since the previous poll. All rates are 0 on the
+    /// first poll of an interface.
+    pub rates: InterfaceCounters<f64>,
+    /// Negotiated link speed in Mbit/s, if the interface has a link, and
+    /// reports one.
+    pub link_speed: Option<u32>,
+    /// RFC 2863 operational state, such as `up`, `down`, or `unknown`.
+    pub operstate: String,
+    pub addresses: Vec<IpAddr>,
+    pub is_loopback: bool,
+    /// Whether the interface is not backed by a device, such as a bridge,
+    /// veth pair, or tunnel.
+    pub is_virtual: bool,
+}
+
+/// [`NetworkPoller`] reads the state and traffic counters of every network
+/// interface found under `<sysfs root>/class/net`.
+///
+/// Since rates are computed from the difference between two polls, the first
+/// poll always reports rates of 0.
+pub struct NetworkPoller {
+    sysfs_root: PathBuf,
+    last_totals: HashMap<String, InterfaceCounters<u64>>,
+    last_time: Instant,
+}
+
+impl Default for NetworkPoller {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl NetworkPoller {
+    /// Create a new poller reading from `/sys`.
+    pub fn new() -> Self {
+        Self::with_sysfs_root("/sys")
+    }
+
+    /// Create a new poller reading from the given sysfs root.
+    pub fn with_sysfs_root(root: impl Into<PathBuf>) -> Self {
+        NetworkPoller {
+            sysfs_root: root.into(),
+            last_totals: HashMap::new(),
+            last_time: Instant::now(),
+        }
+    }
+
+    /// Obtain [`NetworkPollResult`] readings for all network interfaces,
+    /// ordered by name.
+    pub fn poll(&mut self) -> Vec<NetworkPollResult> {
+        let Ok(entries) = fs::read_dir(self.sysfs_root.join("class/net")) else {
+            return vec![];
+        };
+
+        let now = Instant::now();
+        let seconds = now.duration_since(self.last_time).as_secs_f64();
+        let mut addresses = interface_addresses();
+
+        let mut interfaces: Vec<NetworkPollResult> = entries
+            .flatten()
+            .map(|e| {
+                let name = e.file_name().to_string_lossy().to_string();
+                let path = e.path();
+                let stat = |counter: &str| {
+                    read_value::<u64>(path.join("statistics").join(counter)).unwrap_or(0)
+                };
+
+                let totals = InterfaceCounters {
+                    rx_bytes: stat("rx_bytes"),
+                    tx_bytes: stat("tx_bytes"),
+                    rx_packets: stat("rx_packets"),
+                    tx_packets: stat("tx_packets"),
+                    rx_errors: stat("rx_errors"),
+                    tx_errors: stat("tx_errors"),
+                    rx_dropped: stat("rx_dropped"),
+                    tx_dropped: stat("tx_dropped"),
+                };
+
+                let rates = self
+                    .last_totals
+                    .get(&name)
+                    .map(|last| totals.rates_since(last, seconds))
+                    .unwrap_or_default();
+
+                NetworkPollResult {
+                    // Reading speed fails with EINVAL when there is no link,
+                    // and virtual interfaces report -1.
+                    link_speed: read_value::<i64>(path.join("speed"))
+                        .filter(|s| *s > 0)
+                        .map(|s| s as u32),
+                    operstate: read_string(path.join("operstate"))
+                        .unwrap_or_else(|| "unknown".to_string()),
+                    addresses: addresses.remove(&name).unwrap_or_default(),
+                    is_loopback: read_value(path.join("type")) == Some(LOOPBACK_TYPE),
+                    // Interfaces in /sys/class/net link to their device, which
+                    // lives under /sys/devices/virtual if there is none.
+                    is_virtual: fs::read_link(&path)
+                        .is_ok_and(|target| target.to_string_lossy().contains("devices/virtual/")),
+                    name,
+                    totals,
+                    rates,
+                }
+            })
+            .collect();
+
+        interfaces.sort_by(|a, b| a.name.cmp(&b.name));
+
+        self.last_totals = interfaces
+            .iter()
+            .map(|i| (i.name.clone(), i.totals))
+            .collect();
+        self.last_time = now;
+
+        interfaces
+    }
+}
+
+/// Collect the ip addresses of every network interface, by name.
+pub fn interface_addresses() -> HashMap<String, Vec<IpAddr>> {
+    let mut addresses = HashMap::<String, Vec<IpAddr>>::new();
+    let mut ifaddrs: *mut libc::ifaddrs = std::ptr::null_mut();
+
+    // SAFETY: on success, getifaddrs points ifaddrs to a list which is freed
+    // below.
+    if unsafe { libc::getifaddrs(&mut ifaddrs) } != 0 {
+        return addresses;
+    }
+
+    let mut next = ifaddrs;
+
+    while !next.is_null() {
+        // SAFETY: next is a non-null node of the list returned by getifaddrs.
+        let ifa = unsafe { &*next };
+        next = ifa.ifa_next;
+
+        if ifa.ifa_addr.is_null() {
+            continue;
+        }
+
+        // SAFETY: ifa_addr is non-null, and sa_family tells which sockaddr
+        // struct it points to.
+        let addr = unsafe {
+            match (*ifa.ifa_addr).sa_family as i32 {
+                libc::AF_INET => {
+                    let sa = &*(ifa.ifa_addr as *const libc::sockaddr_in);
+                    Some(IpAddr::V4(Ipv4Addr::from(u32::from_be(sa.sin_addr.s_addr))))
+                }
+                libc::AF_INET6 => {
+                    let sa = &*(ifa.ifa_addr as *const libc::sockaddr_in6);
+                    Some(IpAddr::V6(Ipv6Addr::from(sa.sin6_addr.s6_addr)))
+                }
+                _ => None,
+            }
+        };
+
+        if let Some(addr) = addr {
+            // SAFETY: ifa_name is a valid nul terminated string.
+            let name = unsafe { CStr::from_ptr(ifa.ifa_name) };
+
+            addresses
+                .entry(name.to_string_lossy().to_string())
+                .or_default()
+                .push(addr);
+        }
+    }
+
+    // SAFETY: ifaddrs was returned by a successful call to getifaddrs.
+    unsafe { libc::freeifaddrs(ifaddrs) };
+
+    addresses
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+
+    fn fixture_root() -> String {
+        format!("{}/tests/fixtures/sys", env!("CARGO_MANIFEST_DIR"))
+    }
+
+    #[test]
+    fn test_poll_fixture() {
+        let interfaces = NetworkPoller::with_sysfs_root(fixture_root()).poll();
+
+        let names: Vec<&str> = interfaces.iter().map(|i| i.name.as_str()).collect();
+        assert!(names == ["docker0", "eth0", "lo"]);
+
+        let (docker, eth, lo) = (&interfaces[0], &interfaces[1], &interfaces[2]);
+
+        assert!(eth.operstate == "up");
+        assert!(eth.link_speed == Some(1000));
+        assert!(eth.totals.rx_bytes == 123456789);
+        assert!(eth.totals.rx_errors == 3);
+        assert!(eth.totals.rx_dropped == 7);
+        assert!(eth.rates == InterfaceCounters::default());
+        assert!(!eth.is_loopback && !eth.is_virtual);
+
+        assert!(docker.link_speed.is_none());
+        assert!(docker.is_virtual && !docker.is_loopback);
+
+        assert!(lo.is_loopback && lo.is_virtual);
+    }
+
+    #[test]
+    fn test_rates_since() {
+        let previous = InterfaceCounters {
+            rx_bytes: 1000,
+            tx_bytes: 5000,
+            ..Default::default()
+        };
+        let current = InterfaceCounters {
+            rx_bytes: 3000,
+            // The interface was recreated, and its counters reset.
+            tx_bytes: 10,
+            ..Default::default()
+        };
+
+        let rates = current.rates_since(&previous, 2.0);
+
+        assert!(rates.rx_bytes == 1000.0);
+        assert!(rates.tx_bytes == 0.0);
+    }
+
+    #[test]
+    fn test_interface_addresses() {
+        if let Some(addresses) = interface_addresses().get("lo") {
+            assert!(addresses.iter().all(|a| a.is_loopback()));
+        }
+    }
+}
@@ -0,0 +1,208 @@
+/// Module owner contains functionality to attribute processes to the systemd
+/// unit, container, or kubernetes pod which owns them, from the path of
+/// their cgroup.
+use std::{fmt, path::Path};
+
+use super::{cgroup::cgroup_path, cgroup_tree::unescape_unit, sysfs::read_string};
+
+/// Prefixes of the scopes container runtimes create for each container, and
+/// the name of the runtime.
+const CONTAINER_SCOPES: [(&str, &str); 4] = [
+    ("docker-", "docker"),
+    ("libpod-", "podman"),
+    ("cri-containerd-", "containerd"),
+    ("crio-", "cri-o"),
+];
+
+/// Directories container runtimes using the cgroupfs driver create, which
+/// hold a directory named after each container id.
+const CONTAINER_DIRS: [(&str, &str); 2] = [("docker", "docker"), ("libpod_parent", "podman")];
+
+/// Number of characters of a container id shown, as in `docker ps`.
+const SHORT_ID_LENGTH: usize = 12;
+
+/// Enumerates the owners a process can be attributed to.
+#[derive(Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum ProcessOwner {
+    /// A kubernetes pod, by uid.
+    Pod(String),
+    /// A container, by the name of its runtime, and its short id.
+    Container { runtime: &'static str, id: String },
+    /// A systemd service, or scope, such as `cron.service`, or the
+    /// `session-3.scope` of a user session.
+    Unit(String),
+}
+
+impl fmt::Display for ProcessOwner {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ProcessOwner::Pod(uid) => write!(f, "pod {}", uid),
+            ProcessOwner::Container { runtime, id } => write!(f, "{} {}", runtime, id),
+            ProcessOwner::Unit(name) => write!(f, "{}", name),
+        }
+    }
+}
+
+/// Find the runtime, and id of a container from the name of its scope, such
+/// as `docker-<id>.scope`.
+pub fn container_scope(name: &str) -> Option<(&'static str, &str)> {
+    let id = name.strip_suffix(".scope")?;
+
+    CONTAINER_SCOPES
+        .iter()
+        .find_map(|(prefix, runtime)| Some((*runtime, id.strip_prefix(prefix)?)))
+}
+
+/// Shorten a container id to the length shown by container runtimes.
+pub fn short_container_id(id: &str) -> String {
+    id.chars().take(SHORT_ID_LENGTH).collect()
+}
+
+/// Attribute a cgroup path, such as `/system.slice/cron.service`, to its
+/// owner.
+///
+/// Pods take precedence over the containers in them, and containers over
+/// the units they run in. Otherwise, the innermost service, or scope is the
+/// owner. Returns `None` for cgroups outside of any unit, such as the root
+/// cgroup of kernel threads.
+pub fn owner_from_cgroup(path: &str) -> Option<ProcessOwner> {
+    let components: Vec<&str> = path.split('/').filter(|c| !c.is_empty()).collect();
+
+    // With the systemd driver, pods are slices such as
+    // `kubepods-burstable-pod<uid>.slice`, with `-` in the uid replaced by
+    // `_`. With the cgroupfs driver, they are directories named `pod<uid>`.
+    let pod = components
+        .iter()
+        .skip_while(|c| !c.starts_with("kubepods"))
+        .find_map(|c| {
+            let uid = match c.strip_suffix(".slice") {
+                Some(slice) => slice.rsplit_once("-pod")?.1,
+                None => c.strip_prefix("pod")?,
+            };
+
+            (!uid.is_empty()).then(|| uid.replace('_', "-"))
+        });
+
+    if let Some(uid) = pod {
+        return Some(ProcessOwner::Pod(uid));
+    }
+
+    let container = components
+        .iter()
+        .find_map(|c| container_scope(c))
+        .or_else(|| {
+            components.windows(2).find_map(|w| {
+                CONTAINER_DIRS
+                    .iter()
+                    .find_map(|(dir, runtime)| (w[0] == *dir).then_some((*runtime, w[1])))
+            })
+        });
+
+    if let Some((runtime, id)) = container {
+        return Some(ProcessOwner::Container {
+            runtime,
+            id: short_container_id(id),
+        });
+    }
+
+    components
+        .iter()
+        .rev()
+        .find(|c| c.ends_with(".service") || c.ends_with(".scope"))
+        .map(|c| ProcessOwner::Unit(unescape_unit(c)))
+}
+
+/// Read the owner of a process from `<procfs root>/<pid>/cgroup`.
+///
+/// The cgroup v2 path is used where there is one. On cgroup v1 systems, the
+/// path of the systemd hierarchy is used instead.
+pub fn read_process_owner(procfs_root: &Path, pid: u32) -> Option<ProcessOwner> {
+    let contents = read_string(procfs_root.join(pid.to_string()).join("cgroup"))?;
+
+    let path = cgroup_path(&contents, "")
+        .filter(|p| *p != "/")
+        .or_else(|| cgroup_path(&contents, "name=systemd"))?;
+
+    owner_from_cgroup(path)
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+
+    fn fixture_root() -> String {
+        format!("{}/tests/fixtures/proc", env!("CARGO_MANIFEST_DIR"))
+    }
+
+    fn unit(name: &str) -> Option<ProcessOwner> {
+        Some(ProcessOwner::Unit(name.to_string()))
+    }
+
+    #[test]
+    fn test_owner_from_cgroup() {
+        assert!(owner_from_cgroup("/system.slice/cron.service") == unit("cron.service"));
+        assert!(
+            owner_from_cgroup("/user.slice/user-1000.slice/session-3.scope")
+                == unit("session-3.scope")
+        );
+        assert!(
+            owner_from_cgroup(
+                "/user.slice/user-1000.slice/user@1000.service/app.slice/foo.service"
+            ) == unit("foo.service")
+        );
+        assert!(owner_from_cgroup("/").is_none());
+        assert!(owner_from_cgroup("/user.slice").is_none());
+    }
+
+    #[test]
+    fn test_containers() {
+        let docker = Some(ProcessOwner::Container {
+            runtime: "docker",
+            id: "0123456789ab".to_string(),
+        });
+
+        assert!(owner_from_cgroup("/system.slice/docker-0123456789abcdef.scope") == docker);
+        assert!(owner_from_cgroup("/docker/0123456789abcdef") == docker);
+        assert!(
+            owner_from_cgroup("/machine.slice/libpod-fedcba9876543210.scope/container")
+                .is_some_and(|o| o.to_string() == "podman fedcba987654")
+        );
+    }
+
+    #[test]
+    fn test_pods() {
+        let pod = Some(ProcessOwner::Pod(
+            "5f2d7c1e-8a3b-4c9d-9e0f-123456789abc".to_string(),
+        ));
+
+        assert!(
+            owner_from_cgroup(
+                "/kubepods.slice/kubepods-burstable.slice/\
+                 kubepods-burstable-pod5f2d7c1e_8a3b_4c9d_9e0f_123456789abc.slice/\
+                 cri-containerd-0123456789abcdef.scope"
+            ) == pod
+        );
+        assert!(
+            owner_from_cgroup("/kubepods/besteffort/pod5f2d7c1e-8a3b-4c9d-9e0f-123456789abc/0123")
+                == pod
+        );
+
+        // The slice of a qos class is not a pod.
+        assert!(owner_from_cgroup("/kubepods.slice/kubepods-burstable.slice").is_none());
+    }
+
+    #[test]
+    fn test_read_process_owner() {
+        let root = fixture_root();
+        let root = Path::new(&root);
+
+        // A cgroup v2 path.
+        assert!(read_process_owner(root, 4321)
+            .is_some_and(|o| o.to_string() == "pod 5f2d7c1e-8a3b-4c9d-9e0f-123456789abc"));
+
+        // A cgroup v1 system, where the path of the systemd hierarchy is used.
+        assert!(read_process_owner(root, 1234) == unit("sshd.service"));
+
+        assert!(read_process_owner(root, 99999).is_none());
+    }
+}
@@ -1,9 +1,25 @@
 /// module polling contains funcionality to poll system resources.
+use std::path::Path;
 use std::time::Instant;
 
-use nvml_wrapper::{enum_wrappers::device::TemperatureSensor, Nvml};
 use systemstat::Platform;
 
+use super::battery::{BatteryPollResult, BatteryPoller, BatteryStatus};
+use super::cgroup::{CgroupLimits, CgroupPoller};
+use super::cgroup_tree::{CgroupNode, CgroupTreePoller};
+use super::cpufreq::{CpuFrequency, CpuFrequencyPoller};
+use super::diskio::{DiskIoPollResult, DiskIoPoller};
+use super::energy::{EnergyMeter, SessionEnergy};
+use super::filesystem;
+use super::gpu::{self, GpuBackend, GpuError, GpuHealth};
+use super::memory::{MemoryPollResult, MemoryPoller};
+use super::network::{NetworkPollResult, NetworkPoller};
+use super::owner;
+use super::pressure::{PressurePollResult, PressurePoller};
+use super::process::ProcessPollResult;
+use super::procstat::{CpuTimeBreakdown, ProcStatPoller, SystemActivity};
+use super::rapl::{self, RaplDomain, RaplDomainKind, RaplPoller};
+use super::sensors::{self, Sensor, SensorChip, SensorKind, SensorPoller};
 use super::system::{DiskInformation, SystemInformation};
 
 /// [`SystemPollerTarget`] enum allows selection of specific targets when performing
@@ -11,17 +27,51 @@ use super::system::{DiskInformation, SystemInformation};
 ///
 /// The following polling targets are available:
 /// - [`Self::CpuUsage`] current usage percentages of available cores.
-/// - [`Self::CpuTemperature`] current average cpu temperature.
-/// - [`Self::Gpu`] current usage stats about available gpus. (NOTE: Due to
-/// limitations of nvidia's available monitoring packages, all gpu information
-/// has to be polled at once)
-/// - [`Self::Memory`] total and available RAM
+/// - [`Self::CpuTimes`] breakdown of the time each cpu spent in user,
+///   system, iowait, steal, and other states.
+/// - [`Self::CpuTemperature`] current cpu package temperature.
+/// - [`Self::CpuPower`] power consumption of each cpu package, and its
+///   core, uncore, and DRAM domains.
+/// - [`Self::CpuFrequency`] current frequency, and frequency scaling policy of
+///   each logical cpu.
+/// - [`Self::Gpu`] current usage stats about available gpus, from every
+///   configured [`GpuBackend`].
+/// - [`Self::Memory`] total and available RAM, a breakdown of its use, and
+///   swap usage.
+/// - [`Self::Cgroup`] memory, and cpu limits of the cgroup mainframe runs
+///   in, such as those of a container, and its usage against them.
+/// - [`Self::CgroupTree`] cpu, memory, io, and task usage of every cgroup in
+///   the cgroup v2 hierarchy, and the pressure of the cgroup selected with
+///   [`SystemPoller::set_selected_cgroup()`]. Walking the hierarchy can be
+///   paused with [`SystemPoller::set_cgroup_tree_enabled()`].
+/// - [`Self::Processes`] currently running processes, and their resource usage.
+/// - [`Self::Network`] state, and throughput of network interfaces.
+/// - [`Self::DiskIo`] throughput, latency, and utilization of block devices.
+/// - [`Self::Activity`] load averages, uptime, and scheduler statistics.
+/// - [`Self::Pressure`] share of time tasks were stalled on cpu, memory, and
+///   io, from pressure stall information.
+/// - [`Self::Battery`] charge, power draw, and health of system batteries,
+///   and whether an AC adapter is plugged in.
+/// - [`Self::Sensors`] temperature, fan, voltage, and power sensors of every
+///   hardware monitoring chip.
 #[derive(Debug, Clone, Copy)]
 pub enum SystemPollerTarget {
     CpuUsage,
+    CpuTimes,
     CpuTemperature,
+    CpuPower,
+    CpuFrequency,
     Gpu,
     Memory,
+    Cgroup,
+    CgroupTree,
+    Processes,
+    Network,
+    DiskIo,
+    Activity,
+    Pressure,
+    Battery,
+    Sensors,
 }
 
 /// SystemPollResult struct holds the latest polled system data, and is
@@ -33,28 +83,116 @@ pub enum SystemPollerTarget {
 #[derive(Debug, Clone)]
 pub struct SystemPollResult {
     pub cpu_usage: Vec<Measurement>,
-    pub cpu_temperature: Measurement,
+    pub cpu_times: Vec<CpuTimeBreakdown>,
+    /// Cpu temperature, with the limits reported by its sensor, or `None`
+    /// if it could not be read.
+    pub cpu_temperature: Option<Sensor>,
+    pub cpu_frequency: Vec<CpuFrequency>,
+    /// Power of each RAPL domain.
+    pub cpu_power: Vec<RaplDomain>,
     pub memory_usage: Measurement,
+    /// Detailed memory, and swap usage.
+    pub memory: MemoryPollResult,
+    /// Limits of the cgroup mainframe runs in, or `None` if cgroups are not
+    /// available.
+    pub cgroup: Option<CgroupLimits>,
+    /// Every cgroup of the cgroup v2 hierarchy, with parents before their
+    /// children.
+    pub cgroup_tree: Vec<CgroupNode>,
+    /// Pressure of the selected cgroup.
+    pub cgroup_pressure: PressurePollResult,
     pub gpu_info: Vec<GpuPollResult>,
+    /// Errors reported by gpu backends during the last poll.
+    pub gpu_errors: Vec<GpuError>,
+    pub processes: Vec<ProcessPollResult>,
+    pub network: Vec<NetworkPollResult>,
+    pub disk_io: Vec<DiskIoPollResult>,
+    pub activity: SystemActivity,
+    pub pressure: PressurePollResult,
+    pub battery: BatteryPollResult,
+    /// Energy used since the poller was created. This is updated on every
+    /// poll, from the power readings of whichever targets are polled.
+    pub energy: SessionEnergy,
+    pub sensors: Vec<SensorChip>,
 }
 
 impl Default for SystemPollResult {
     /// Return a 0-initialized [`SystemPollResult`] object.
     fn default() -> Self {
         SystemPollResult {
-            cpu_usage: vec![Measurement::default(); 0],
-            cpu_temperature: Measurement::default(),
+            cpu_usage: vec![],
+            cpu_times: vec![],
+            cpu_temperature: None,
+            cpu_frequency: vec![],
+            cpu_power: vec![],
             memory_usage: Measurement::default(),
+            memory: MemoryPollResult::default(),
+            cgroup: None,
+            cgroup_tree: vec![],
+            cgroup_pressure: PressurePollResult::default(),
             gpu_info: vec![],
+            gpu_errors: vec![],
+            processes: vec![],
+            network: vec![],
+            disk_io: vec![],
+            activity: SystemActivity::default(),
+            pressure: PressurePollResult::default(),
+            battery: BatteryPollResult::default(),
+            energy: SessionEnergy::default(),
+            sensors: vec![],
         }
     }
 }
 
-/// [`GpuPollResult`] contains gpu device data obtained by polling.
+impl SystemPollResult {
+    /// Power consumption of the cpus, DRAM, and gpus in watts, or `None` if
+    /// none of them report their power.
+    ///
+    /// This is the platform power if the cpu reports it, or the power of
+    /// every cpu package, and DRAM, plus the power of every gpu. The core,
+    /// and uncore domains are left out, as they are part of their package.
+    pub fn measured_power(&self) -> Option<f32> {
+        let cpu = rapl::total_watts(&self.cpu_power, RaplDomainKind::Psys).or_else(|| {
+            [RaplDomainKind::Package, RaplDomainKind::Dram]
+                .into_iter()
+                .filter_map(|kind| rapl::total_watts(&self.cpu_power, kind))
+                .reduce(|a, b| a + b)
+        });
+
+        let gpu = self
+            .gpu_info
+            .iter()
+            .filter_map(|g| g.power_usage)
+            .reduce(|a, b| a + b);
+
+        [cpu, gpu].into_iter().flatten().reduce(|a, b| a + b)
+    }
+
+    /// Estimated power consumption of the whole machine in watts, used to
+    /// integrate the energy used, or `None` if no power reading is
+    /// available.
+    ///
+    /// When running on battery, this is the power drawn from the batteries,
+    /// which covers the whole machine. Otherwise, it is the
+    /// [`Self::measured_power()`].
+    pub fn estimated_machine_power(&self) -> Option<f32> {
+        let battery = self
+            .battery
+            .batteries
+            .iter()
+            .filter(|b| b.status == BatteryStatus::Discharging)
+            .filter_map(|b| b.power)
+            .reduce(|a, b| a + b);
+
+        battery.or_else(|| self.measured_power())
+    }
+}
+
+/// [`GpuPollResult`] contains gpu device data obtained by polling a single
+/// device of a [`GpuBackend`].
 ///
-/// Due to the implementation of nvidia packages, it is not possible to filter
-/// for specific device information per-poll, and so a single gpu poll returns
-/// all device information.
+/// Optional fields are `None` when the device, or its driver, does not
+/// support the corresponding query.
 #[derive(Clone, Debug)]
 pub struct GpuPollResult {
     pub name: String,
@@ -62,6 +200,30 @@ pub struct GpuPollResult {
     pub usage: f32,
     pub memory_total: u64,
     pub memory_used: u64,
+    /// Current power draw in watts.
+    pub power_usage: Option<f32>,
+    /// Power limit enforced by the driver, in watts.
+    pub power_limit: Option<f32>,
+    /// Current fan speed, as a percentage of its maximum.
+    pub fan_speed: Option<u32>,
+    /// Current graphics clock in MHz. On Nvidia devices, this is the SM
+    /// clock.
+    pub graphics_clock: Option<u32>,
+    /// Current memory clock in MHz.
+    pub memory_clock: Option<u32>,
+    /// PCIe transmit throughput, in bytes per second.
+    pub pcie_tx: Option<u64>,
+    /// PCIe receive throughput, in bytes per second.
+    pub pcie_rx: Option<u64>,
+    /// Video encoder utilization percentage.
+    pub encoder_usage: Option<u32>,
+    /// Video decoder utilization percentage.
+    pub decoder_usage: Option<u32>,
+    /// Processes using this device. Only backends which can attribute usage
+    /// to processes fill this in.
+    pub processes: Vec<GpuProcess>,
+    /// Throttling, and memory error diagnostics.
+    pub health: GpuHealth,
 }
 
 impl Default for GpuPollResult {
@@ -72,10 +234,33 @@ impl Default for GpuPollResult {
             usage: 0f32,
             memory_total: 0u64,
             memory_used: 0u64,
+            power_usage: None,
+            power_limit: None,
+            fan_speed: None,
+            graphics_clock: None,
+            memory_clock: None,
+            pcie_tx: None,
+            pcie_rx: None,
+            encoder_usage: None,
+            decoder_usage: None,
+            processes: vec![],
+            health: GpuHealth::default(),
         }
     }
 }
 
+/// [`GpuProcess`] describes a process using a gpu device.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct GpuProcess {
+    pub pid: u32,
+    pub name: String,
+    /// Device memory used by the process, in bytes.
+    pub memory_used: Option<u64>,
+    /// Share of the device's SM (3D and compute) time used by the process,
+    /// as a percentage.
+    pub sm_usage: Option<u32>,
+}
+
 /// SystemPoller manages the polling of system data.
 ///
 /// System data includes metrics like cpu usage, temperature, memory
@@ -89,7 +274,25 @@ impl Default for GpuPollResult {
 pub struct SystemPoller {
     sysinfo_system: sysinfo::System,
     systemstat_system: systemstat::System,
-    nvml: Option<Nvml>,
+    sysinfo_users: sysinfo::Users,
+    gpu_backends: Vec<Box<dyn GpuBackend>>,
+    cpu_frequency: CpuFrequencyPoller,
+    proc_stat: ProcStatPoller,
+    rapl: RaplPoller,
+    memory: MemoryPoller,
+    cgroup: CgroupPoller,
+    cgroup_tree: CgroupTreePoller,
+    /// Path of the cgroup whose pressure is polled, relative to the root of
+    /// the cgroup v2 hierarchy.
+    selected_cgroup: Option<String>,
+    /// Whether [`SystemPollerTarget::CgroupTree`] is polled.
+    cgroup_tree_enabled: bool,
+    network: NetworkPoller,
+    disk_io: DiskIoPoller,
+    pressure: PressurePoller,
+    battery: BatteryPoller,
+    energy: EnergyMeter,
+    sensors: SensorPoller,
     target_flags: Vec<SystemPollerTarget>,
 }
 
@@ -109,10 +312,22 @@ impl SystemPoller {
         SystemPoller {
             sysinfo_system,
             systemstat_system: systemstat::System::new(),
-            nvml: match Nvml::init() {
-                Ok(nvml) => Some(nvml),
-                Err(_) => None,
-            },
+            sysinfo_users: sysinfo::Users::new_with_refreshed_list(),
+            gpu_backends: gpu::default_backends(),
+            cpu_frequency: CpuFrequencyPoller::new(),
+            proc_stat: ProcStatPoller::new(),
+            rapl: RaplPoller::new(),
+            memory: MemoryPoller::new(),
+            cgroup: CgroupPoller::new(),
+            cgroup_tree: CgroupTreePoller::new(),
+            selected_cgroup: None,
+            cgroup_tree_enabled: true,
+            network: NetworkPoller::new(),
+            disk_io: DiskIoPoller::new(),
+            pressure: PressurePoller::new(),
+            battery: BatteryPoller::new(),
+            energy: EnergyMeter::new(),
+            sensors: SensorPoller::new(),
             target_flags: vec![],
         }
     }
@@ -150,6 +365,34 @@ impl SystemPoller {
         self
     }
 
+    /// Replace the gpu backends used for [`SystemPollerTarget::Gpu`].
+    ///
+    /// By default, every backend returned by [`gpu::default_backends()`] is
+    /// used.
+    pub fn with_gpu_backends(mut self, backends: Vec<Box<dyn GpuBackend>>) -> Self {
+        self.gpu_backends = backends;
+
+        self
+    }
+
+    /// Select the cgroup whose pressure is polled by
+    /// [`SystemPollerTarget::CgroupTree`], as a path relative to the root of
+    /// the cgroup v2 hierarchy, such as `/system.slice`.
+    pub fn set_selected_cgroup(&mut self, path: Option<String>) {
+        self.selected_cgroup = path;
+    }
+
+    /// Pause, or resume polling [`SystemPollerTarget::CgroupTree`], which
+    /// reads every cgroup on the system. While paused, the tree is left
+    /// empty, and usage rates start over once it is resumed.
+    pub fn set_cgroup_tree_enabled(&mut self, enabled: bool) {
+        if !enabled {
+            self.cgroup_tree.reset();
+        }
+
+        self.cgroup_tree_enabled = enabled;
+    }
+
     /// Poll the system for each of the previously defined poll targets.
     ///
     /// See [`Self::with_poll_targets()`] for more details about selecting poll targets.
@@ -157,7 +400,11 @@ impl SystemPoller {
         let mut res = SystemPollResult::default();
         let time = TimePoint(Instant::now());
 
-        for k in self.target_flags.as_slice() {
+        // Sensors are read at most once per poll, as both the cpu
+        // temperature, and sensors targets use them.
+        let mut sensor_chips: Option<Vec<SensorChip>> = None;
+
+        for k in self.target_flags.clone() {
             match k {
                 // Fetch cpu usage
                 SystemPollerTarget::CpuUsage => {
@@ -171,15 +418,29 @@ impl SystemPoller {
                         });
                     }
                 }
+                SystemPollerTarget::CpuTimes => res.cpu_times = self.proc_stat.poll(),
                 SystemPollerTarget::CpuTemperature => {
-                    res.cpu_temperature = Measurement {
-                        time,
-                        name: "".into(),
-                        value: self.systemstat_system.cpu_temp().unwrap_or(0f32),
-                    };
-                    // println!("Polled temp: {:?}", res.cpu_temperature);
+                    // hwmon reports the critical temperature along with the
+                    // reading, so it is preferred when available.
+                    let chips = sensor_chips.get_or_insert_with(|| self.sensors.poll());
+
+                    res.cpu_temperature = sensors::cpu_temperature(chips).cloned().or_else(|| {
+                        let temp = self.systemstat_system.cpu_temp().ok()?;
+
+                        Some(Sensor {
+                            kind: SensorKind::Temperature,
+                            label: "cpu".to_string(),
+                            value: temp as f64,
+                            max: None,
+                            critical: None,
+                        })
+                    });
+                }
+                SystemPollerTarget::CpuPower => res.cpu_power = self.rapl.poll(),
+                SystemPollerTarget::CpuFrequency => res.cpu_frequency = self.cpu_frequency.poll(),
+                SystemPollerTarget::Gpu => {
+                    (res.gpu_info, res.gpu_errors) = gpu::poll_backends(&mut self.gpu_backends)
                 }
-                SystemPollerTarget::Gpu => res.gpu_info = self.poll_gpus(),
                 SystemPollerTarget::Memory => {
                     self.sysinfo_system.refresh_memory();
 
@@ -187,11 +448,37 @@ impl SystemPoller {
                         time,
                         name: "memory".to_string(),
                         value: self.sysinfo_system.used_memory() as f32,
+                    };
+
+                    res.memory = self.memory.poll();
+                }
+                SystemPollerTarget::Cgroup => res.cgroup = self.cgroup.poll(),
+                SystemPollerTarget::CgroupTree if self.cgroup_tree_enabled => {
+                    res.cgroup_tree = self.poll_cgroup_tree();
+
+                    if let Some(path) = &self.selected_cgroup {
+                        res.cgroup_pressure = self.pressure.poll_cgroup(path);
                     }
                 }
+                SystemPollerTarget::CgroupTree => (),
+                SystemPollerTarget::Processes => res.processes = self.poll_processes(),
+                SystemPollerTarget::Network => res.network = self.network.poll(),
+                SystemPollerTarget::DiskIo => res.disk_io = self.disk_io.poll(),
+                SystemPollerTarget::Activity => res.activity = self.proc_stat.poll_activity(),
+                SystemPollerTarget::Pressure => res.pressure = self.pressure.poll(),
+                SystemPollerTarget::Battery => res.battery = self.battery.poll(),
+                SystemPollerTarget::Sensors => {
+                    res.sensors = sensor_chips
+                        .get_or_insert_with(|| self.sensors.poll())
+                        .clone()
+                }
             }
         }
 
+        // Energy is integrated from the power readings of this poll, so it
+        // is only computed once every target has been polled.
+        res.energy = self.energy.add(res.estimated_machine_power(), time.0);
+
         res
     }
 
@@ -212,66 +499,59 @@ impl SystemPoller {
         }
     }
 
-    /// Construct a disk data object for each disk.
+    /// Construct a disk data object for each mounted filesystem.
     pub fn get_disk_info(&mut self) -> Vec<DiskInformation> {
-        let mut disks = Vec::<DiskInformation>::new();
-
-        for disk in &sysinfo::Disks::new_with_refreshed_list() {
-            disks.push(DiskInformation {
-                name: disk.name().to_string_lossy().to_string(),
-                kind: match disk.kind() {
-                    sysinfo::DiskKind::SSD => "SSD".to_string(),
-                    sysinfo::DiskKind::HDD => "HDD".to_string(),
-                    sysinfo::DiskKind::Unknown(s) => format!("??? ({})", s),
-                },
-                available_space: disk.total_space() - disk.available_space(),
-                total_space: disk.total_space(),
-            });
-        }
-
-        disks
+        filesystem::read_disks()
     }
 
-    /// Obtain [`GpuPollResult`] readings for all available gpu devices.
-    fn poll_gpus(&self) -> Vec<GpuPollResult> {
-        match &self.nvml {
-            None => vec![],
-            Some(nvml) => {
-                let mut gpus = Vec::<GpuPollResult>::new();
-                gpus.reserve(nvml.device_count().unwrap() as usize);
-
-                for i in 0..nvml.device_count().unwrap() {
-                    let device = nvml.device_by_index(i).unwrap();
-
-                    let memory_info = device.memory_info();
-
-                    gpus.push(GpuPollResult {
-                        name: match device.name() {
-                            Ok(n) => n,
-                            Err(e) => e.to_string(),
-                        },
-                        temp: match device.temperature(TemperatureSensor::Gpu) {
-                            Ok(t) => t as f32,
-                            Err(_) => 0f32,
-                        },
-                        usage: match device.utilization_rates() {
-                            Ok(r) => r.gpu as f32,
-                            Err(_) => 0f32,
-                        },
-                        memory_total: match &memory_info {
-                            Ok(m) => m.total,
-                            Err(_) => 0,
-                        },
-                        memory_used: match memory_info {
-                            Ok(m) => m.used,
-                            Err(_) => 0,
-                        },
-                    })
-                }
-
-                gpus
+    /// Obtain [`CgroupNode`] readings for every cgroup, with the uids of user
+    /// slices, and user managers resolved to user names.
+    fn poll_cgroup_tree(&mut self) -> Vec<CgroupNode> {
+        let mut nodes = self.cgroup_tree.poll();
+
+        for node in nodes.iter_mut() {
+            let Some(uid) = node.uid else {
+                continue;
+            };
+
+            if let Some(user) = self.sysinfo_users.iter().find(|u| **u.id() == uid) {
+                node.description = node
+                    .description
+                    .as_ref()
+                    .map(|d| d.replace(&uid.to_string(), user.name()));
             }
         }
+
+        nodes
+    }
+
+    /// Obtain [`ProcessPollResult`] readings for all running processes.
+    ///
+    /// Userland threads are reported by sysinfo alongside processes, and are
+    /// filtered out here. The owner of each process is read from its cgroup.
+    fn poll_processes(&mut self) -> Vec<ProcessPollResult> {
+        self.sysinfo_system.refresh_processes();
+
+        self.sysinfo_system
+            .processes()
+            .values()
+            .filter(|process| process.thread_kind() != Some(sysinfo::ThreadKind::Userland))
+            .map(|process| ProcessPollResult {
+                pid: process.pid().as_u32(),
+                parent_pid: process.parent().map(|p| p.as_u32()),
+                name: process.name().to_string(),
+                user: process
+                    .user_id()
+                    .and_then(|uid| self.sysinfo_users.get_user_by_id(uid))
+                    .map(|user| user.name().to_string())
+                    .unwrap_or_else(|| "???".to_string()),
+                owner: owner::read_process_owner(Path::new("/proc"), process.pid().as_u32()),
+                cpu_usage: process.cpu_usage(),
+                memory: process.memory(),
+                state: process.status().to_string(),
+                start_time: process.start_time(),
+            })
+            .collect()
     }
 }
 
@@ -303,3 +583,60 @@ impl Default for Measurement {
         }
     }
 }
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+
+    #[test]
+    fn test_estimated_machine_power() {
+        use crate::monitoring::battery::Battery;
+
+        let domain = |kind, watts| RaplDomain {
+            zone: String::new(),
+            kind,
+            package: 0,
+            watts: Some(watts),
+        };
+
+        let mut res = SystemPollResult {
+            cpu_power: vec![
+                domain(RaplDomainKind::Package, 30.0),
+                domain(RaplDomainKind::Core, 20.0),
+                domain(RaplDomainKind::Dram, 5.0),
+            ],
+            ..Default::default()
+        };
+
+        assert!(res.estimated_machine_power() == Some(35.0));
+
+        res.gpu_info.push(GpuPollResult {
+            power_usage: Some(100.0),
+            ..Default::default()
+        });
+
+        assert!(res.estimated_machine_power() == Some(135.0));
+
+        // Platform power already covers the packages, and DRAM.
+        res.cpu_power.push(domain(RaplDomainKind::Psys, 50.0));
+
+        assert!(res.estimated_machine_power() == Some(150.0));
+
+        // A discharging battery powers the whole machine.
+        res.battery.batteries.push(Battery {
+            name: "BAT0".to_string(),
+            status: BatteryStatus::Discharging,
+            capacity: 50.0,
+            power: Some(12.5),
+            energy_now: None,
+            energy_full: None,
+            energy_full_design: None,
+        });
+
+        assert!(res.estimated_machine_power() == Some(12.5));
+        assert!(res.measured_power() == Some(150.0));
+        assert!(SystemPollResult::default()
+            .estimated_machine_power()
+            .is_none());
+    }
+}
@@ -0,0 +1,185 @@
+/// Module pressure contains functionality to poll pressure stall information
+/// (PSI), system wide from `/proc/pressure`, or for a single cgroup.
+use std::{fs, path::Path, path::PathBuf};
+
+use super::cgroup::unified_root;
+
+/// [`PressureAverages`] holds the share of time, as a percentage, during
+/// which tasks were stalled on a resource, averaged over three windows.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct PressureAverages {
+    pub avg10: f32,
+    pub avg60: f32,
+    pub avg300: f32,
+    /// Total stall time, in microseconds.
+    pub total: u64,
+}
+
+/// [`ResourcePressure`] holds the pressure on a single resource.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct ResourcePressure {
+    /// Time during which at least some tasks were stalled.
+    pub some: PressureAverages,
+    /// Time during which all non-idle tasks were stalled at once. Not
+    /// reported for cpu pressure by older kernels.
+    pub full: Option<PressureAverages>,
+}
+
+/// [`PressurePollResult`] contains the pressure on each resource. Resources
+/// are `None` if the kernel does not support PSI, or it is disabled.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct PressurePollResult {
+    pub cpu: Option<ResourcePressure>,
+    pub memory: Option<ResourcePressure>,
+    pub io: Option<ResourcePressure>,
+}
+
+impl PressurePollResult {
+    /// Each resource which reported its pressure, with its name.
+    pub fn resources(&self) -> Vec<(&'static str, &ResourcePressure)> {
+        [
+            ("cpu", &self.cpu),
+            ("memory", &self.memory),
+            ("io", &self.io),
+        ]
+        .into_iter()
+        .filter_map(|(name, pressure)| Some((name, pressure.as_ref()?)))
+        .collect()
+    }
+}
+
+/// Parse the contents of a pressure file, such as `/proc/pressure/io`.
+///
+/// Returns `None` if the `some` line is missing, or malformed.
+pub fn parse_pressure(contents: &str) -> Option<ResourcePressure> {
+    let parse_line = |kind: &str| -> Option<PressureAverages> {
+        let line = contents.lines().find(|l| l.starts_with(kind))?;
+        let mut averages = PressureAverages::default();
+
+        for field in line.split_whitespace().skip(1) {
+            let (key, value) = field.split_once('=')?;
+
+            match key {
+                "avg10" => averages.avg10 = value.parse().ok()?,
+                "avg60" => averages.avg60 = value.parse().ok()?,
+                "avg300" => averages.avg300 = value.parse().ok()?,
+                "total" => averages.total = value.parse().ok()?,
+                _ => (),
+            }
+        }
+
+        Some(averages)
+    };
+
+    Some(ResourcePressure {
+        some: parse_line("some ")?,
+        full: parse_line("full "),
+    })
+}
+
+/// Read the pressure files of each resource, named `<resource><suffix>`, in
+/// the given directory.
+fn read_pressure(dir: &Path, suffix: &str) -> PressurePollResult {
+    let read = |resource: &str| {
+        let contents = fs::read_to_string(dir.join(format!("{}{}", resource, suffix))).ok()?;
+
+        parse_pressure(&contents)
+    };
+
+    PressurePollResult {
+        cpu: read("cpu"),
+        memory: read("memory"),
+        io: read("io"),
+    }
+}
+
+/// [`PressurePoller`] reads system wide pressure from
+/// `<procfs root>/pressure`, and the pressure of individual cgroups from
+/// their directory under the cgroup v2 root.
+pub struct PressurePoller {
+    procfs_root: PathBuf,
+    cgroup_root: PathBuf,
+}
+
+impl Default for PressurePoller {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl PressurePoller {
+    /// Create a new poller reading from `/proc`, and `/sys/fs/cgroup`.
+    pub fn new() -> Self {
+        Self::with_roots("/proc", "/sys/fs/cgroup")
+    }
+
+    /// Create a new poller reading from the given procfs, and cgroup v2
+    /// roots.
+    pub fn with_roots(procfs_root: impl Into<PathBuf>, cgroup_root: impl Into<PathBuf>) -> Self {
+        PressurePoller {
+            procfs_root: procfs_root.into(),
+            cgroup_root: cgroup_root.into(),
+        }
+    }
+
+    /// Obtain a system wide [`PressurePollResult`] reading.
+    pub fn poll(&self) -> PressurePollResult {
+        read_pressure(&self.procfs_root.join("pressure"), "")
+    }
+
+    /// Obtain a [`PressurePollResult`] reading for the cgroup at `path`,
+    /// relative to the root of the cgroup v2 hierarchy, such as
+    /// `system.slice`.
+    pub fn poll_cgroup(&self, path: &str) -> PressurePollResult {
+        let Some(root) = unified_root(&self.cgroup_root) else {
+            return PressurePollResult::default();
+        };
+
+        read_pressure(&root.join(path.trim_start_matches('/')), ".pressure")
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+
+    fn fixture_root(dir: &str) -> String {
+        format!("{}/tests/fixtures/{}", env!("CARGO_MANIFEST_DIR"), dir)
+    }
+
+    fn poller() -> PressurePoller {
+        PressurePoller::with_roots(fixture_root("proc"), fixture_root("sys/fs/cgroup"))
+    }
+
+    #[test]
+    fn test_parse_pressure() {
+        let pressure = parse_pressure("some avg10=1.31 avg60=2.82 avg300=2.79 total=72872833\n");
+
+        assert!(pressure.is_some_and(|p| p.some.avg60 == 2.82 && p.full.is_none()));
+        assert!(pressure.is_some_and(|p| p.some.total == 72872833));
+        assert!(parse_pressure("some avg10=x\n").is_none());
+        assert!(parse_pressure("").is_none());
+    }
+
+    #[test]
+    fn test_poll_fixture() {
+        let pressure = poller().poll();
+
+        assert!(pressure.resources().len() == 3);
+        assert!(pressure.memory.is_some_and(|m| m.some.avg10 == 12.5));
+        assert!(pressure
+            .io
+            .is_some_and(|io| io.full.is_some_and(|f| f.avg300 == 15.0)));
+    }
+
+    #[test]
+    fn test_poll_cgroup_fixture() {
+        let pressure = poller().poll_cgroup("/system.slice");
+
+        assert!(pressure
+            .cpu
+            .is_some_and(|c| c.some.avg10 == 0.5 && c.full.is_none()));
+        assert!(pressure.io.is_some_and(|io| io.some.avg10 == 2.0));
+        assert!(poller().poll_cgroup("missing.slice").resources().is_empty());
+    }
+}
@@ -0,0 +1,461 @@
+/// Module process contains data types describing running processes, and
+/// helpers for ordering them, and arranging them into a tree.
+use std::cmp::Ordering;
+use std::collections::{HashMap, HashSet};
+
+use super::owner::ProcessOwner;
+
+/// [`ProcessPollResult`] contains information about a single running process
+/// obtained by polling.
+#[derive(Clone, Debug)]
+pub struct ProcessPollResult {
+    pub pid: u32,
+    pub parent_pid: Option<u32>,
+    pub name: String,
+    pub user: String,
+    /// Systemd unit, container, or pod the process belongs to, if any.
+    pub owner: Option<ProcessOwner>,
+    pub cpu_usage: f32,
+    pub memory: u64,
+    pub state: String,
+    pub start_time: u64,
+}
+
+impl Default for ProcessPollResult {
+    fn default() -> Self {
+        ProcessPollResult {
+            pid: 0,
+            parent_pid: None,
+            name: "???".to_string(),
+            user: "???".to_string(),
+            owner: None,
+            cpu_usage: 0f32,
+            memory: 0u64,
+            state: "???".to_string(),
+            start_time: 0u64,
+        }
+    }
+}
+
+/// [`ProcessSortColumn`] enumerates the columns a process list can be sorted by.
+///
+/// Variants are declared in the order in which they are displayed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProcessSortColumn {
+    Pid,
+    Name,
+    User,
+    Owner,
+    CpuUsage,
+    Memory,
+    State,
+    StartTime,
+}
+
+impl ProcessSortColumn {
+    /// All sort columns, in display order.
+    pub const ALL: [ProcessSortColumn; 8] = [
+        ProcessSortColumn::Pid,
+        ProcessSortColumn::Name,
+        ProcessSortColumn::User,
+        ProcessSortColumn::Owner,
+        ProcessSortColumn::CpuUsage,
+        ProcessSortColumn::Memory,
+        ProcessSortColumn::State,
+        ProcessSortColumn::StartTime,
+    ];
+
+    /// Human readable column title.
+    pub fn title(&self) -> &'static str {
+        match self {
+            ProcessSortColumn::Pid => "PID",
+            ProcessSortColumn::Name => "Name",
+            ProcessSortColumn::User => "User",
+            ProcessSortColumn::Owner => "Owner",
+            ProcessSortColumn::CpuUsage => "CPU %",
+            ProcessSortColumn::Memory => "RSS",
+            ProcessSortColumn::State => "State",
+            ProcessSortColumn::StartTime => "Started",
+        }
+    }
+
+    /// Get the column after this one, wrapping around to the first column.
+    pub fn next(&self) -> Self {
+        let i = Self::ALL.iter().position(|c| c == self).unwrap_or(0);
+
+        Self::ALL[(i + 1) % Self::ALL.len()]
+    }
+
+    /// Get the column before this one, wrapping around to the last column.
+    pub fn prev(&self) -> Self {
+        let i = Self::ALL.iter().position(|c| c == self).unwrap_or(0);
+
+        Self::ALL[(i + Self::ALL.len() - 1) % Self::ALL.len()]
+    }
+
+    /// Compare two processes by the value held in this column.
+    pub fn compare(&self, a: &ProcessPollResult, b: &ProcessPollResult) -> Ordering {
+        match self {
+            ProcessSortColumn::Pid => a.pid.cmp(&b.pid),
+            ProcessSortColumn::Name => a.name.to_lowercase().cmp(&b.name.to_lowercase()),
+            ProcessSortColumn::User => a.user.cmp(&b.user),
+            ProcessSortColumn::Owner => a.owner.cmp(&b.owner),
+            ProcessSortColumn::CpuUsage => a.cpu_usage.total_cmp(&b.cpu_usage),
+            ProcessSortColumn::Memory => a.memory.cmp(&b.memory),
+            ProcessSortColumn::State => a.state.cmp(&b.state),
+            ProcessSortColumn::StartTime => a.start_time.cmp(&b.start_time),
+        }
+    }
+}
+
+/// Sort the given process list in place by `column`.
+///
+/// Ties are broken by pid so that the resulting order is stable between polls.
+pub fn sort_processes(
+    processes: &mut [ProcessPollResult],
+    column: ProcessSortColumn,
+    descending: bool,
+) {
+    processes.sort_by(|a, b| {
+        let o = column.compare(a, b).then_with(|| a.pid.cmp(&b.pid));
+
+        match descending {
+            true => o.reverse(),
+            false => o,
+        }
+    });
+}
+
+/// [`ProcessGroup`] contains the processes which belong to the same owner,
+/// as grouped by [`group_processes()`].
+#[derive(Clone, Debug)]
+pub struct ProcessGroup {
+    /// Owner shared by every process of the group. Processes without an
+    /// owner are grouped together.
+    pub owner: Option<ProcessOwner>,
+    pub processes: Vec<ProcessPollResult>,
+    /// Cpu usage of every process of the group.
+    pub cpu_usage: f32,
+    /// Memory of every process of the group.
+    pub memory: u64,
+}
+
+impl ProcessGroup {
+    /// Name of the group, which identifies it.
+    pub fn name(&self) -> String {
+        group_name(self.owner.as_ref())
+    }
+}
+
+/// Name of the group of processes with the given owner.
+pub fn group_name(owner: Option<&ProcessOwner>) -> String {
+    match owner {
+        Some(owner) => owner.to_string(),
+        None => "(no owner)".to_string(),
+    }
+}
+
+/// Group the given processes by owner.
+///
+/// Processes within a group are ordered by `column`. When sorting by cpu
+/// usage or memory, groups are ordered by their totals, so that the heaviest
+/// owners are listed first. Otherwise, groups are ordered by owner.
+pub fn group_processes(
+    processes: &[ProcessPollResult],
+    column: ProcessSortColumn,
+    descending: bool,
+) -> Vec<ProcessGroup> {
+    let mut groups: HashMap<Option<&ProcessOwner>, Vec<ProcessPollResult>> = HashMap::new();
+
+    for p in processes {
+        groups.entry(p.owner.as_ref()).or_default().push(p.clone());
+    }
+
+    let mut groups: Vec<ProcessGroup> = groups
+        .into_iter()
+        .map(|(owner, mut processes)| {
+            sort_processes(&mut processes, column, descending);
+
+            ProcessGroup {
+                owner: owner.cloned(),
+                cpu_usage: processes.iter().map(|p| p.cpu_usage).sum(),
+                memory: processes.iter().map(|p| p.memory).sum(),
+                processes,
+            }
+        })
+        .collect();
+
+    groups.sort_by(|a, b| {
+        let o = match column {
+            ProcessSortColumn::CpuUsage => a.cpu_usage.total_cmp(&b.cpu_usage),
+            ProcessSortColumn::Memory => a.memory.cmp(&b.memory),
+            _ => a.owner.cmp(&b.owner),
+        }
+        .then_with(|| a.owner.cmp(&b.owner));
+
+        match descending {
+            true => o.reverse(),
+            false => o,
+        }
+    });
+
+    groups
+}
+
+/// [`ProcessTreeRow`] is a single row of a process tree, flattened into
+/// display order by [`build_process_tree()`].
+#[derive(Clone, Debug)]
+pub struct ProcessTreeRow {
+    pub process: ProcessPollResult,
+    /// Number of ancestors between this process and its root.
+    pub depth: usize,
+    pub has_children: bool,
+    pub collapsed: bool,
+    /// Cpu usage of this process and all of its descendants.
+    pub subtree_cpu_usage: f32,
+    /// Memory of this process and all of its descendants.
+    pub subtree_memory: u64,
+}
+
+/// Arrange the given processes into a tree using their parent pids, and
+/// flatten it into display order.
+///
+/// Children are nested directly below their parent, and siblings are ordered
+/// by `column`. When sorting by cpu usage or memory, siblings are ordered by
+/// the totals of their whole subtree, so that the heaviest process trees are
+/// listed first.
+///
+/// Descendants of any process whose pid is in `collapsed` are omitted from
+/// the result, though they still count towards its subtree totals.
+pub fn build_process_tree(
+    processes: &[ProcessPollResult],
+    column: ProcessSortColumn,
+    descending: bool,
+    collapsed: &HashSet<u32>,
+) -> Vec<ProcessTreeRow> {
+    let index: HashMap<u32, usize> = processes
+        .iter()
+        .enumerate()
+        .map(|(i, p)| (p.pid, i))
+        .collect();
+
+    let mut children = vec![Vec::<usize>::new(); processes.len()];
+    let mut roots = Vec::<usize>::new();
+
+    for (i, p) in processes.iter().enumerate() {
+        match p.parent_pid.and_then(|ppid| index.get(&ppid)) {
+            Some(&parent) if parent != i => children[parent].push(i),
+            _ => roots.push(i),
+        }
+    }
+
+    // Visit every process, parents before children. Roots are visited first,
+    // after which any process left unvisited must have parents forming a
+    // cycle, which is broken by detaching it from its parent.
+    let mut visited = vec![false; processes.len()];
+    let mut order = Vec::<usize>::with_capacity(processes.len());
+    let candidates: Vec<usize> = roots.iter().copied().chain(0..processes.len()).collect();
+    let root_count = roots.len();
+
+    for (n, root) in candidates.into_iter().enumerate() {
+        if visited[root] {
+            continue;
+        }
+
+        if n >= root_count {
+            children.iter_mut().for_each(|c| c.retain(|c| *c != root));
+            roots.push(root);
+        }
+
+        let mut stack = vec![root];
+
+        while let Some(i) = stack.pop() {
+            if !visited[i] {
+                visited[i] = true;
+                order.push(i);
+                stack.extend(children[i].iter().copied());
+            }
+        }
+    }
+
+    // Accumulate subtree totals, children before parents.
+    let mut totals: Vec<(f32, u64)> = processes.iter().map(|p| (p.cpu_usage, p.memory)).collect();
+
+    for &i in order.iter().rev() {
+        let (cpu, memory) = children[i].iter().fold(totals[i], |acc, c| {
+            (acc.0 + totals[*c].0, acc.1 + totals[*c].1)
+        });
+        totals[i] = (cpu, memory);
+    }
+
+    let compare = |a: &usize, b: &usize| {
+        let o = match column {
+            ProcessSortColumn::CpuUsage => totals[*a].0.total_cmp(&totals[*b].0),
+            ProcessSortColumn::Memory => totals[*a].1.cmp(&totals[*b].1),
+            _ => column.compare(&processes[*a], &processes[*b]),
+        }
+        .then_with(|| processes[*a].pid.cmp(&processes[*b].pid));
+
+        match descending {
+            true => o.reverse(),
+            false => o,
+        }
+    };
+
+    roots.sort_by(compare);
+    children.iter_mut().for_each(|c| c.sort_by(compare));
+
+    // Flatten the tree depth first, pushing children in reverse so that they
+    // are popped in sorted order.
+    let mut rows = Vec::<ProcessTreeRow>::with_capacity(processes.len());
+    let mut stack: Vec<(usize, usize)> = roots.iter().rev().map(|r| (*r, 0)).collect();
+
+    while let Some((i, depth)) = stack.pop() {
+        let p = &processes[i];
+        let is_collapsed = collapsed.contains(&p.pid);
+
+        rows.push(ProcessTreeRow {
+            process: p.clone(),
+            depth,
+            has_children: !children[i].is_empty(),
+            collapsed: is_collapsed,
+            subtree_cpu_usage: totals[i].0,
+            subtree_memory: totals[i].1,
+        });
+
+        if !is_collapsed {
+            stack.extend(children[i].iter().rev().map(|c| (*c, depth + 1)));
+        }
+    }
+
+    rows
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+
+    fn process(pid: u32, name: &str, cpu_usage: f32) -> ProcessPollResult {
+        ProcessPollResult {
+            pid,
+            name: name.to_string(),
+            cpu_usage,
+            ..Default::default()
+        }
+    }
+
+    fn child(pid: u32, parent_pid: u32, cpu_usage: f32) -> ProcessPollResult {
+        ProcessPollResult {
+            parent_pid: Some(parent_pid),
+            ..process(pid, "child", cpu_usage)
+        }
+    }
+
+    #[test]
+    fn test_tree_nesting_and_totals() {
+        let p = vec![
+            process(1, "init", 1.0),
+            child(10, 1, 2.0),
+            child(11, 10, 30.0),
+            child(20, 1, 5.0),
+        ];
+
+        let rows = build_process_tree(&p, ProcessSortColumn::CpuUsage, true, &HashSet::new());
+
+        let layout: Vec<(u32, usize)> = rows.iter().map(|r| (r.process.pid, r.depth)).collect();
+        assert!(layout == vec![(1, 0), (10, 1), (11, 2), (20, 1)]);
+
+        assert!(rows[0].subtree_cpu_usage == 38.0);
+        assert!(rows[1].subtree_cpu_usage == 32.0);
+        assert!(rows[0].has_children && !rows[2].has_children);
+    }
+
+    #[test]
+    fn test_tree_collapse() {
+        let p = vec![
+            process(1, "init", 1.0),
+            child(10, 1, 2.0),
+            child(11, 10, 3.0),
+        ];
+
+        let collapsed = HashSet::from([10]);
+        let rows = build_process_tree(&p, ProcessSortColumn::Pid, false, &collapsed);
+
+        assert!(rows.len() == 2);
+        assert!(rows[1].collapsed);
+        assert!(rows[1].subtree_cpu_usage == 5.0);
+    }
+
+    #[test]
+    fn test_tree_orphans_and_cycles() {
+        let p = vec![child(5, 99, 0.0), child(6, 7, 0.0), child(7, 6, 0.0)];
+
+        let rows = build_process_tree(&p, ProcessSortColumn::Pid, false, &HashSet::new());
+
+        let layout: Vec<(u32, usize)> = rows.iter().map(|r| (r.process.pid, r.depth)).collect();
+        assert!(layout == vec![(5, 0), (6, 0), (7, 1)]);
+    }
+
+    #[test]
+    fn test_sort_by_cpu() {
+        let mut p = vec![
+            process(1, "a", 5.0),
+            process(2, "b", 50.0),
+            process(3, "c", 0.5),
+        ];
+
+        sort_processes(&mut p, ProcessSortColumn::CpuUsage, true);
+
+        assert!(p.iter().map(|p| p.pid).collect::<Vec<_>>() == vec![2, 1, 3]);
+    }
+
+    #[test]
+    fn test_sort_by_name_ascending() {
+        let mut p = vec![
+            process(1, "zsh", 0.0),
+            process(2, "Bash", 0.0),
+            process(3, "cat", 0.0),
+        ];
+
+        sort_processes(&mut p, ProcessSortColumn::Name, false);
+
+        assert!(p.iter().map(|p| p.pid).collect::<Vec<_>>() == vec![2, 3, 1]);
+    }
+
+    #[test]
+    fn test_group_by_owner() {
+        let owned = |pid: u32, owner: &str, cpu_usage: f32| ProcessPollResult {
+            owner: Some(ProcessOwner::Unit(owner.to_string())),
+            ..process(pid, "p", cpu_usage)
+        };
+
+        let p = vec![
+            owned(1, "a.service", 5.0),
+            owned(2, "b.service", 20.0),
+            owned(3, "a.service", 10.0),
+            process(4, "kthread", 1.0),
+        ];
+
+        let groups = group_processes(&p, ProcessSortColumn::CpuUsage, true);
+
+        let layout: Vec<(String, Vec<u32>)> = groups
+            .iter()
+            .map(|g| {
+                let owner = g.owner.as_ref().map(|o| o.to_string()).unwrap_or_default();
+                (owner, g.processes.iter().map(|p| p.pid).collect())
+            })
+            .collect();
+
+        assert!(layout[0] == ("b.service".to_string(), vec![2]));
+        assert!(layout[1] == ("a.service".to_string(), vec![3, 1]));
+        assert!(layout[2] == (String::new(), vec![4]));
+        assert!(groups[1].cpu_usage == 15.0);
+    }
+
+    #[test]
+    fn test_column_cycle() {
+        assert!(ProcessSortColumn::StartTime.next() == ProcessSortColumn::Pid);
+        assert!(ProcessSortColumn::Pid.prev() == ProcessSortColumn::StartTime);
+        assert!(ProcessSortColumn::Name.next() == ProcessSortColumn::User);
+    }
+}
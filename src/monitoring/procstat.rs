@@ -0,0 +1,344 @@
+/// Module procstat contains functionality to poll cpu time accounting, and
+/// scheduler activity from `/proc/stat`, `/proc/loadavg`, and `/proc/uptime`.
+use std::{collections::HashMap, fs, path::PathBuf, time::Instant};
+
+use super::sysfs::read_string;
+
+/// [`CpuTimes`] holds the cumulative time a cpu has spent in each state, in
+/// clock ticks, as read from one `cpu` line of `/proc/stat`.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct CpuTimes {
+    /// Time spent in user mode, including [`Self::guest`].
+    pub user: u64,
+    /// Time spent in user mode at low priority, including
+    /// [`Self::guest_nice`].
+    pub nice: u64,
+    pub system: u64,
+    pub idle: u64,
+    pub iowait: u64,
+    pub irq: u64,
+    pub softirq: u64,
+    pub steal: u64,
+    pub guest: u64,
+    pub guest_nice: u64,
+}
+
+impl CpuTimes {
+    /// Total time accounted for. Guest time is already included in user and
+    /// nice time, so it is not counted again.
+    fn total(&self) -> u64 {
+        self.user
+            + self.nice
+            + self.system
+            + self.idle
+            + self.iowait
+            + self.irq
+            + self.softirq
+            + self.steal
+    }
+}
+
+/// Parse the per cpu lines of `/proc/stat`, such as `cpu0`, ordered by cpu
+/// index. The line summing up all cpus is skipped.
+///
+/// Older kernels report fewer columns, which are read as 0.
+pub fn parse_cpu_times(contents: &str) -> Vec<(String, CpuTimes)> {
+    contents
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.split_whitespace();
+            let name = fields.next()?;
+
+            // Skip other lines, and the aggregate cpu line.
+            name.strip_prefix("cpu")?.parse::<usize>().ok()?;
+
+            let values: Vec<u64> = fields.map(|f| f.parse().unwrap_or(0)).collect();
+            let value = |i: usize| values.get(i).copied().unwrap_or(0);
+
+            Some((
+                name.to_string(),
+                CpuTimes {
+                    user: value(0),
+                    nice: value(1),
+                    system: value(2),
+                    idle: value(3),
+                    iowait: value(4),
+                    irq: value(5),
+                    softirq: value(6),
+                    steal: value(7),
+                    guest: value(8),
+                    guest_nice: value(9),
+                },
+            ))
+        })
+        .collect()
+}
+
+/// Parse the single value lines of `/proc/stat`, such as `ctxt` and
+/// `procs_running`, into values by name. For `intr`, and `softirq`, only the
+/// leading total is kept.
+pub fn parse_stat_counters(contents: &str) -> HashMap<&str, u64> {
+    contents
+        .lines()
+        .filter(|line| !line.starts_with("cpu"))
+        .filter_map(|line| {
+            let mut fields = line.split_whitespace();
+
+            Some((fields.next()?, fields.next()?.parse().ok()?))
+        })
+        .collect()
+}
+
+/// [`SystemActivity`] contains system wide load, and scheduler statistics
+/// obtained by polling.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct SystemActivity {
+    /// Load averages over the last 1, 5, and 15 minutes.
+    pub load_average: [f32; 3],
+    pub uptime_seconds: u64,
+    /// Number of runnable tasks.
+    pub procs_running: u64,
+    /// Number of tasks blocked waiting for io.
+    pub procs_blocked: u64,
+    /// Rates since the previous poll. All rates are 0 on the first poll.
+    pub context_switches_per_sec: f64,
+    pub forks_per_sec: f64,
+    pub interrupts_per_sec: f64,
+}
+
+/// [`CpuTimeBreakdown`] contains the percentage of time a cpu spent in each
+/// state between two polls. Percentages add up to 100.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct CpuTimeBreakdown {
+    pub name: String,
+    /// User time, excluding guest time.
+    pub user: f32,
+    /// Low priority user time, excluding guest time.
+    pub nice: f32,
+    pub system: f32,
+    pub idle: f32,
+    pub iowait: f32,
+    pub irq: f32,
+    pub softirq: f32,
+    /// Time the hypervisor spent running other virtual machines, while this
+    /// cpu wanted to run.
+    pub steal: f32,
+    /// Time spent running guest virtual machines, at any priority.
+    pub guest: f32,
+}
+
+impl CpuTimeBreakdown {
+    /// Compute the time breakdown of a cpu from two samples of its times.
+    ///
+    /// A cpu for which no time has passed between the samples is reported
+    /// as idle.
+    pub fn from_times(name: String, previous: &CpuTimes, current: &CpuTimes) -> Self {
+        let delta = |f: fn(&CpuTimes) -> u64| f(current).saturating_sub(f(previous));
+        let total = delta(CpuTimes::total) as f32;
+
+        if total == 0f32 {
+            return CpuTimeBreakdown {
+                name,
+                idle: 100f32,
+                ..Default::default()
+            };
+        }
+
+        let percent = |ticks: u64| 100f32 * ticks as f32 / total;
+
+        let (guest, guest_nice) = (delta(|t| t.guest), delta(|t| t.guest_nice));
+
+        CpuTimeBreakdown {
+            name,
+            user: percent(delta(|t| t.user).saturating_sub(guest)),
+            nice: percent(delta(|t| t.nice).saturating_sub(guest_nice)),
+            system: percent(delta(|t| t.system)),
+            idle: percent(delta(|t| t.idle)),
+            iowait: percent(delta(|t| t.iowait)),
+            irq: percent(delta(|t| t.irq)),
+            softirq: percent(delta(|t| t.softirq)),
+            steal: percent(delta(|t| t.steal)),
+            guest: percent(guest + guest_nice),
+        }
+    }
+
+    /// Percentage of time spent in any state other than idle, or waiting for
+    /// io.
+    pub fn busy(&self) -> f32 {
+        100f32 - self.idle - self.iowait
+    }
+}
+
+/// [`ProcStatPoller`] reads cpu times, and scheduler activity from
+/// `<procfs root>/stat`, along with the load average, and uptime.
+///
+/// The first poll of cpu times reports the breakdown since boot, and later
+/// polls the breakdown since the previous poll.
+pub struct ProcStatPoller {
+    procfs_root: PathBuf,
+    last_times: HashMap<String, CpuTimes>,
+    /// Context switch, fork, and interrupt counters as of the last poll of
+    /// system activity.
+    last_counters: Option<[u64; 3]>,
+    last_time: Instant,
+}
+
+impl Default for ProcStatPoller {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ProcStatPoller {
+    /// Create a new poller reading from `/proc`.
+    pub fn new() -> Self {
+        Self::with_procfs_root("/proc")
+    }
+
+    /// Create a new poller reading from the given procfs root.
+    pub fn with_procfs_root(root: impl Into<PathBuf>) -> Self {
+        ProcStatPoller {
+            procfs_root: root.into(),
+            last_times: HashMap::new(),
+            last_counters: None,
+            last_time: Instant::now(),
+        }
+    }
+
+    /// Obtain a [`CpuTimeBreakdown`] reading for each cpu, ordered by cpu
+    /// index.
+    pub fn poll(&mut self) -> Vec<CpuTimeBreakdown> {
+        let Ok(contents) = fs::read_to_string(self.procfs_root.join("stat")) else {
+            return vec![];
+        };
+
+        let times = parse_cpu_times(&contents);
+
+        let breakdown = times
+            .iter()
+            .map(|(name, current)| {
+                let previous = self.last_times.get(name).copied().unwrap_or_default();
+
+                CpuTimeBreakdown::from_times(name.clone(), &previous, current)
+            })
+            .collect();
+
+        self.last_times = times.into_iter().collect();
+
+        breakdown
+    }
+
+    /// Obtain a [`SystemActivity`] reading.
+    ///
+    /// Values which could not be read are left at 0.
+    pub fn poll_activity(&mut self) -> SystemActivity {
+        let stat = fs::read_to_string(self.procfs_root.join("stat")).unwrap_or_default();
+        let stat = parse_stat_counters(&stat);
+        let counter = |name: &str| stat.get(name).copied().unwrap_or(0);
+
+        let loadavg = read_string(self.procfs_root.join("loadavg")).unwrap_or_default();
+        let mut loads = loadavg
+            .split_whitespace()
+            .map(|l| l.parse::<f32>().unwrap_or(0f32));
+
+        let now = Instant::now();
+        let seconds = now
+            .duration_since(self.last_time)
+            .as_secs_f64()
+            .max(f64::EPSILON);
+
+        let counters = [counter("ctxt"), counter("processes"), counter("intr")];
+        let rates = match self.last_counters {
+            Some(last) => [0, 1, 2].map(|i| counters[i].saturating_sub(last[i]) as f64 / seconds),
+            None => [0f64; 3],
+        };
+
+        self.last_counters = Some(counters);
+        self.last_time = now;
+
+        SystemActivity {
+            load_average: [0, 1, 2].map(|_| loads.next().unwrap_or(0f32)),
+            uptime_seconds: read_string(self.procfs_root.join("uptime"))
+                .and_then(|u| u.split_whitespace().next()?.parse::<f64>().ok())
+                .unwrap_or(0f64) as u64,
+            procs_running: counter("procs_running"),
+            procs_blocked: counter("procs_blocked"),
+            context_switches_per_sec: rates[0],
+            forks_per_sec: rates[1],
+            interrupts_per_sec: rates[2],
+        }
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+
+    fn fixture_root() -> String {
+        format!("{}/tests/fixtures/proc", env!("CARGO_MANIFEST_DIR"))
+    }
+
+    #[test]
+    fn test_parse_cpu_times() {
+        let times = parse_cpu_times("cpu  1 2 3 4\ncpu0 1 2 3 4\ncpu1 5 6 7\nintr 1 2\n");
+
+        assert!(times.iter().map(|t| t.0.as_str()).collect::<Vec<_>>() == ["cpu0", "cpu1"]);
+        assert!(times[0].1.idle == 4 && times[0].1.steal == 0);
+        assert!(times[1].1.system == 7);
+    }
+
+    #[test]
+    fn test_from_times() {
+        let previous = CpuTimes::default();
+        let current = CpuTimes {
+            user: 50,
+            system: 10,
+            idle: 20,
+            iowait: 5,
+            steal: 15,
+            guest: 20,
+            ..Default::default()
+        };
+
+        let cpu = CpuTimeBreakdown::from_times("cpu0".to_string(), &previous, &current);
+
+        assert!(cpu.user == 30.0);
+        assert!(cpu.guest == 20.0);
+        assert!(cpu.steal == 15.0);
+        assert!(cpu.busy() == 75.0);
+    }
+
+    #[test]
+    fn test_poll_fixture() {
+        let mut poller = ProcStatPoller::with_procfs_root(fixture_root());
+
+        let cpus = poller.poll();
+
+        assert!(cpus.len() == 2);
+        assert!(cpus[0].name == "cpu0");
+        assert!(cpus[0].steal > 0.0 && cpus[0].iowait > 0.0);
+
+        // Nothing changed since the first poll.
+        assert!(poller.poll()[0].busy() == 0.0);
+    }
+
+    #[test]
+    fn test_poll_activity_fixture() {
+        let activity = ProcStatPoller::with_procfs_root(fixture_root()).poll_activity();
+
+        assert!(activity.load_average == [0.52, 0.40, 0.31]);
+        assert!(activity.uptime_seconds == 273845);
+        assert!(activity.procs_running == 2);
+        assert!(activity.procs_blocked == 1);
+        assert!(activity.context_switches_per_sec == 0.0);
+    }
+
+    #[test]
+    fn test_parse_stat_counters() {
+        let counters = parse_stat_counters("cpu0 1 2 3\nintr 100 1 2\nctxt 5000\n");
+
+        assert!(counters.len() == 2);
+        assert!(counters["intr"] == 100);
+        assert!(counters["ctxt"] == 5000);
+    }
+}
@@ -0,0 +1,198 @@
+/// Module rapl contains functionality to poll the power consumption of cpu
+/// packages, and memory through the RAPL (running average power limit)
+/// energy counters exposed by `/sys/class/powercap`.
+use std::{collections::HashMap, fs, path::PathBuf, time::Instant};
+
+use super::sysfs::{read_string, read_value};
+
+/// Enumerates the RAPL power domains.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum RaplDomainKind {
+    /// A whole cpu package, including its cores, and uncore.
+    Package,
+    /// The cpu cores of a package.
+    Core,
+    /// The parts of a package outside its cores, such as an integrated gpu.
+    Uncore,
+    Dram,
+    /// The whole platform, as reported by some laptops.
+    Psys,
+}
+
+impl RaplDomainKind {
+    pub const ALL: [RaplDomainKind; 5] = [
+        RaplDomainKind::Package,
+        RaplDomainKind::Core,
+        RaplDomainKind::Uncore,
+        RaplDomainKind::Dram,
+        RaplDomainKind::Psys,
+    ];
+
+    /// Determine the kind of a zone from its name, such as `package-0` or
+    /// `dram`.
+    fn from_name(name: &str) -> Option<Self> {
+        match name {
+            n if n.starts_with("package") => Some(RaplDomainKind::Package),
+            "core" => Some(RaplDomainKind::Core),
+            "uncore" => Some(RaplDomainKind::Uncore),
+            "dram" => Some(RaplDomainKind::Dram),
+            "psys" => Some(RaplDomainKind::Psys),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            RaplDomainKind::Package => "Package",
+            RaplDomainKind::Core => "Core",
+            RaplDomainKind::Uncore => "Uncore",
+            RaplDomainKind::Dram => "DRAM",
+            RaplDomainKind::Psys => "Platform",
+        }
+    }
+}
+
+/// [`RaplDomain`] contains a single power reading of a RAPL domain.
+#[derive(Clone, Debug, PartialEq)]
+pub struct RaplDomain {
+    /// Name of the powercap zone, such as `intel-rapl:0:1`.
+    pub zone: String,
+    pub kind: RaplDomainKind,
+    /// Index of the package the domain belongs to.
+    pub package: u32,
+    /// Average power since the previous poll, in watts. `None` on the first
+    /// poll of a domain.
+    pub watts: Option<f32>,
+}
+
+/// Sum the power of every domain of the given kind, across packages. Returns
+/// `None` if no domain of that kind has a reading.
+pub fn total_watts(domains: &[RaplDomain], kind: RaplDomainKind) -> Option<f32> {
+    domains
+        .iter()
+        .filter(|d| d.kind == kind)
+        .filter_map(|d| d.watts)
+        .reduce(|a, b| a + b)
+}
+
+/// Energy used between two readings of an energy counter, which wraps around
+/// to 0 after reaching `max_range`.
+pub fn energy_delta(previous: u64, current: u64, max_range: u64) -> u64 {
+    match current >= previous {
+        true => current - previous,
+        false => max_range.saturating_sub(previous) + current,
+    }
+}
+
+/// [`RaplPoller`] reads the energy counter of every RAPL zone found under
+/// `<sysfs root>/class/powercap`, and turns them into watts.
+///
+/// Both Intel, and AMD cpus register their zones as `intel-rapl:*`. The
+/// `intel-rapl-mmio` zones duplicate the package counters, and are skipped.
+/// Zones whose counters cannot be read, as is the default for non-root
+/// users on recent kernels, are skipped too.
+pub struct RaplPoller {
+    sysfs_root: PathBuf,
+    /// Energy counter of each zone, in µJ, as of the last poll.
+    last_energy: HashMap<String, (u64, Instant)>,
+}
+
+impl Default for RaplPoller {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl RaplPoller {
+    /// Create a new poller reading from `/sys`.
+    pub fn new() -> Self {
+        Self::with_sysfs_root("/sys")
+    }
+
+    /// Create a new poller reading from the given sysfs root.
+    pub fn with_sysfs_root(root: impl Into<PathBuf>) -> Self {
+        RaplPoller {
+            sysfs_root: root.into(),
+            last_energy: HashMap::new(),
+        }
+    }
+
+    /// Obtain a [`RaplDomain`] reading for every readable zone, ordered by
+    /// zone name.
+    pub fn poll(&mut self) -> Vec<RaplDomain> {
+        let Ok(entries) = fs::read_dir(self.sysfs_root.join("class/powercap")) else {
+            return vec![];
+        };
+
+        let now = Instant::now();
+
+        let mut domains: Vec<RaplDomain> = entries
+            .flatten()
+            .filter_map(|e| {
+                let zone = e.file_name().to_str()?.to_string();
+                let package = zone.strip_prefix("intel-rapl:")?.split(':').next()?;
+                let path = e.path();
+
+                let kind = RaplDomainKind::from_name(&read_string(path.join("name"))?)?;
+                let energy = read_value::<u64>(path.join("energy_uj"))?;
+                let max_range = read_value::<u64>(path.join("max_energy_range_uj")).unwrap_or(0);
+
+                let watts = self.last_energy.get(&zone).map(|(previous, time)| {
+                    let seconds = now.duration_since(*time).as_secs_f32().max(f32::EPSILON);
+
+                    energy_delta(*previous, energy, max_range) as f32 / 1_000_000f32 / seconds
+                });
+
+                self.last_energy.insert(zone.clone(), (energy, now));
+
+                Some(RaplDomain {
+                    package: package.parse().ok()?,
+                    zone,
+                    kind,
+                    watts,
+                })
+            })
+            .collect();
+
+        domains.sort_by(|a, b| a.zone.cmp(&b.zone));
+
+        domains
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+
+    fn fixture_root() -> String {
+        format!("{}/tests/fixtures/sys", env!("CARGO_MANIFEST_DIR"))
+    }
+
+    #[test]
+    fn test_energy_delta() {
+        assert!(energy_delta(100, 250, 1000) == 150);
+
+        // The counter wrapped around past its maximum.
+        assert!(energy_delta(900, 50, 1000) == 150);
+    }
+
+    #[test]
+    fn test_poll_fixture() {
+        let mut poller = RaplPoller::with_sysfs_root(fixture_root());
+
+        let domains = poller.poll();
+
+        // intel-rapl:1 has no readable energy counter.
+        let zones: Vec<&str> = domains.iter().map(|d| d.zone.as_str()).collect();
+        assert!(zones.len() == 4 && zones[3] == "intel-rapl:0:2");
+
+        let kinds: Vec<RaplDomainKind> = domains.iter().map(|d| d.kind).collect();
+        assert!(kinds[1..] == RaplDomainKind::ALL[1..4]);
+        assert!(domains.iter().all(|d| d.package == 0 && d.watts.is_none()));
+        assert!(total_watts(&domains, RaplDomainKind::Package).is_none());
+
+        // Nothing changed since the first poll.
+        let domains = poller.poll();
+        assert!(total_watts(&domains, RaplDomainKind::Dram) == Some(0.0));
+    }
+}
@@ -0,0 +1,281 @@
+/// Module sensors contains functionality to poll hardware monitoring chips,
+/// such as cpu, motherboard, and gpu sensors, through `/sys/class/hwmon`.
+use std::{fs, path::Path, path::PathBuf};
+
+use super::sysfs::{read_string, read_value};
+
+/// Names of hwmon chips which report cpu temperatures.
+static CPU_CHIPS: [&str; 4] = ["coretemp", "k10temp", "zenpower", "cpu_thermal"];
+
+/// Labels of the sensors which best describe the temperature of a whole cpu
+/// package, in order of preference.
+static CPU_PACKAGE_LABELS: [&str; 3] = ["Package id 0", "Tdie", "Tctl"];
+
+/// Enumerates the kinds of hwmon sensor inputs which are read.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SensorKind {
+    Temperature,
+    Fan,
+    Voltage,
+    Power,
+}
+
+impl SensorKind {
+    pub const ALL: [SensorKind; 4] = [
+        SensorKind::Temperature,
+        SensorKind::Fan,
+        SensorKind::Voltage,
+        SensorKind::Power,
+    ];
+
+    /// Prefix of the sysfs attributes of this kind, such as `temp` in
+    /// `temp1_input`.
+    fn prefix(&self) -> &'static str {
+        match self {
+            SensorKind::Temperature => "temp",
+            SensorKind::Fan => "fan",
+            SensorKind::Voltage => "in",
+            SensorKind::Power => "power",
+        }
+    }
+
+    /// Factor by which raw sysfs values are divided, to obtain a value in
+    /// [`Self::unit()`].
+    fn scale(&self) -> f64 {
+        match self {
+            SensorKind::Temperature => 1000f64,
+            SensorKind::Fan => 1f64,
+            SensorKind::Voltage => 1000f64,
+            SensorKind::Power => 1_000_000f64,
+        }
+    }
+
+    /// Unit in which values of this kind are reported.
+    pub fn unit(&self) -> &'static str {
+        match self {
+            SensorKind::Temperature => "C",
+            SensorKind::Fan => "RPM",
+            SensorKind::Voltage => "V",
+            SensorKind::Power => "W",
+        }
+    }
+}
+
+/// [`Sensor`] contains a single reading of a hwmon sensor input.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Sensor {
+    pub kind: SensorKind,
+    /// Label reported by the driver, or the name of the input, such as
+    /// `temp1`, if there is none.
+    pub label: String,
+    /// Current value, in the unit of [`Self::kind`].
+    pub value: f64,
+    pub max: Option<f64>,
+    pub critical: Option<f64>,
+}
+
+impl Sensor {
+    /// Upper bound to draw this sensor's value against, if the driver
+    /// reports one.
+    pub fn upper_bound(&self) -> Option<f64> {
+        self.critical.or(self.max).filter(|b| *b > 0f64)
+    }
+}
+
+/// [`SensorChip`] contains the readings of every sensor of one hwmon chip.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SensorChip {
+    /// Driver name of the chip, such as `coretemp` or `amdgpu`.
+    pub name: String,
+    pub sensors: Vec<Sensor>,
+}
+
+impl SensorChip {
+    /// Whether this chip reports cpu temperatures.
+    pub fn is_cpu(&self) -> bool {
+        CPU_CHIPS.contains(&self.name.as_str())
+    }
+}
+
+/// Find the sensor which best describes the temperature of the cpu, among
+/// the sensors of every chip.
+pub fn cpu_temperature(chips: &[SensorChip]) -> Option<&Sensor> {
+    let temperatures: Vec<&Sensor> = chips
+        .iter()
+        .filter(|c| c.is_cpu())
+        .flat_map(|c| c.sensors.iter())
+        .filter(|s| s.kind == SensorKind::Temperature)
+        .collect();
+
+    CPU_PACKAGE_LABELS
+        .iter()
+        .find_map(|label| temperatures.iter().find(|s| s.label == *label))
+        .or(temperatures.first())
+        .copied()
+}
+
+/// Read every input of the given kind from a hwmon chip directory, ordered
+/// by index.
+fn read_sensors(chip: &Path, kind: SensorKind, files: &[String]) -> Vec<Sensor> {
+    let prefix = kind.prefix();
+
+    let mut indices: Vec<u32> = files
+        .iter()
+        .filter_map(|f| {
+            let index = f.strip_prefix(prefix)?;
+            let index = index
+                .strip_suffix("_input")
+                .or_else(|| index.strip_suffix("_average"))?;
+
+            index.parse().ok()
+        })
+        .collect();
+
+    indices.sort();
+    indices.dedup();
+
+    indices
+        .into_iter()
+        .filter_map(|i| {
+            let name = format!("{}{}", prefix, i);
+            let attribute = |suffix: &str| {
+                read_value::<f64>(chip.join(format!("{}_{}", name, suffix)))
+                    .map(|v| v / kind.scale())
+            };
+
+            // Power is reported either as an instantaneous, or an average
+            // value, depending on the driver.
+            let value = attribute("input").or_else(|| attribute("average"))?;
+
+            Some(Sensor {
+                kind,
+                label: read_string(chip.join(format!("{}_label", name)))
+                    .unwrap_or_else(|| name.clone()),
+                value,
+                max: attribute("max").or_else(|| attribute("cap")),
+                critical: attribute("crit"),
+            })
+        })
+        .collect()
+}
+
+/// [`SensorPoller`] reads every sensor of every hwmon chip found under
+/// `<sysfs root>/class/hwmon`.
+pub struct SensorPoller {
+    sysfs_root: PathBuf,
+}
+
+impl Default for SensorPoller {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SensorPoller {
+    /// Create a new poller reading from `/sys`.
+    pub fn new() -> Self {
+        Self::with_sysfs_root("/sys")
+    }
+
+    /// Create a new poller reading from the given sysfs root.
+    pub fn with_sysfs_root(root: impl Into<PathBuf>) -> Self {
+        SensorPoller {
+            sysfs_root: root.into(),
+        }
+    }
+
+    /// Obtain a [`SensorChip`] reading for every hwmon chip, in the order in
+    /// which the kernel registered them. Chips without any readable sensor
+    /// are skipped.
+    pub fn poll(&self) -> Vec<SensorChip> {
+        let Ok(entries) = fs::read_dir(self.sysfs_root.join("class/hwmon")) else {
+            return vec![];
+        };
+
+        let mut chips: Vec<(u32, PathBuf)> = entries
+            .flatten()
+            .filter_map(|e| {
+                let index = e
+                    .file_name()
+                    .to_str()?
+                    .strip_prefix("hwmon")?
+                    .parse()
+                    .ok()?;
+
+                Some((index, e.path()))
+            })
+            .collect();
+
+        chips.sort_by_key(|(index, _)| *index);
+
+        chips
+            .into_iter()
+            .filter_map(|(_, path)| {
+                let files: Vec<String> = fs::read_dir(&path)
+                    .ok()?
+                    .flatten()
+                    .map(|e| e.file_name().to_string_lossy().to_string())
+                    .collect();
+
+                let sensors: Vec<Sensor> = SensorKind::ALL
+                    .iter()
+                    .flat_map(|kind| read_sensors(&path, *kind, &files))
+                    .collect();
+
+                if sensors.is_empty() {
+                    return None;
+                }
+
+                Some(SensorChip {
+                    name: read_string(path.join("name")).unwrap_or_else(|| "???".to_string()),
+                    sensors,
+                })
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+
+    fn fixture_root() -> String {
+        format!("{}/tests/fixtures/sys", env!("CARGO_MANIFEST_DIR"))
+    }
+
+    #[test]
+    fn test_poll_fixture() {
+        let chips = SensorPoller::with_sysfs_root(fixture_root()).poll();
+
+        let names: Vec<&str> = chips.iter().map(|c| c.name.as_str()).collect();
+        assert!(names == ["coretemp", "nct6798", "amdgpu"]);
+
+        let package = &chips[0].sensors[0];
+        assert!(package.label == "Package id 0");
+        assert!(package.value == 52.0);
+        assert!(package.max == Some(80.0));
+        assert!(package.critical == Some(100.0));
+
+        let board = &chips[1].sensors;
+        let labels: Vec<&str> = board.iter().map(|s| s.label.as_str()).collect();
+        assert!(labels == ["SYSTIN", "fan1", "fan2", "Vcore", "in1"]);
+        assert!(board[1].value == 1200.0);
+        assert!(board[3].value == 1.04);
+        assert!(board[3].upper_bound() == Some(1.744));
+        assert!(board[0].upper_bound().is_none());
+
+        let power = chips[2]
+            .sensors
+            .iter()
+            .find(|s| s.kind == SensorKind::Power);
+        assert!(power.is_some_and(|p| p.value == 187.0));
+    }
+
+    #[test]
+    fn test_cpu_temperature() {
+        let chips = SensorPoller::with_sysfs_root(fixture_root()).poll();
+
+        assert!(cpu_temperature(&chips).is_some_and(|s| s.value == 52.0));
+        assert!(cpu_temperature(&chips[1..]).is_none());
+    }
+}
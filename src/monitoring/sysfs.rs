@@ -0,0 +1,33 @@
+/// Module sysfs contains helpers for reading the small text files exposed by
+/// the linux `/sys` and `/proc` pseudo filesystems.
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+
+/// Read the file at `path`, and parse its trimmed contents as a `T`.
+///
+/// Returns `None` if the file does not exist, cannot be read (as is common
+/// for files which require elevated permissions), or cannot be parsed.
+pub fn read_value<T: FromStr>(path: impl AsRef<Path>) -> Option<T> {
+    read_string(path)?.parse().ok()
+}
+
+/// Read the file at `path`, and return its trimmed contents.
+pub fn read_string(path: impl AsRef<Path>) -> Option<String> {
+    fs::read_to_string(path).ok().map(|s| s.trim().to_string())
+}
+
+/// Find the first hwmon directory belonging to the given sysfs device
+/// directory, such as `/sys/class/drm/card0/device`.
+pub fn find_hwmon(device: &Path) -> Option<PathBuf> {
+    let mut hwmons: Vec<PathBuf> = fs::read_dir(device.join("hwmon"))
+        .ok()?
+        .flatten()
+        .map(|e| e.path())
+        .collect();
+
+    hwmons.sort();
+    hwmons.into_iter().next()
+}
@@ -4,6 +4,10 @@ use super::polling::SystemPoller;
 ///
 /// Static system data refers to data that will not change over the runtime of
 /// the system, such as system name, number of drives, total available RAM, etc.
+///
+/// Disks are the exception, as filesystems may be mounted, unmounted, or
+/// filled up at any time. They should be read again with
+/// [`super::filesystem::read_disks()`] on a slower interval than the regular poll.
 pub struct SystemData {
     pub info: SystemInformation,
     pub disks: Vec<DiskInformation>,
@@ -28,11 +32,30 @@ pub struct SystemInformation {
 ///
 /// Should be instantiated via the appropriate system information monitoring
 /// backend.
+#[derive(Clone, Debug, Default)]
 pub struct DiskInformation {
     pub name: String,
     pub kind: String,
+    pub mount_point: String,
+    pub file_system: String,
+    /// Space available to unprivileged users, in bytes.
     pub available_space: u64,
     pub total_space: u64,
+    pub inodes_total: u64,
+    pub inodes_free: u64,
+    pub read_only: bool,
+}
+
+impl DiskInformation {
+    /// Space which is not available to unprivileged users, in bytes. This
+    /// includes space reserved for root.
+    pub fn used_space(&self) -> u64 {
+        self.total_space.saturating_sub(self.available_space)
+    }
+
+    pub fn inodes_used(&self) -> u64 {
+        self.inodes_total.saturating_sub(self.inodes_free)
+    }
 }
 
 impl SystemData {
@@ -21,8 +21,7 @@ where
 {
     /// Creates a new [`RingBuffer`] object of size `size`.
     pub fn new(size: usize) -> Self {
-        let mut data = Vec::<T>::new();
-        data.reserve(size);
+        let data = Vec::<T>::with_capacity(size);
 
         RingBuffer {
             head: 0,
@@ -44,13 +43,24 @@ where
         self.count = min(self.count + 1, self.size);
     }
 
+    /// Return the most recently added item, if any.
     pub fn last(&self) -> Option<&T> {
         match self.count {
             0 => None,
-            _ => Some(self.peek(self.head)),
+            _ => Some(self.peek(self.head + self.size - 1)),
         }
     }
 
+    /// Iterate over the items of the ring buffer, from oldest to newest.
+    pub fn iter(&self) -> impl Iterator<Item = &T> {
+        let start = match self.count < self.size {
+            true => 0,
+            false => self.head,
+        };
+
+        (0..self.count).map(move |i| self.peek(start + i))
+    }
+
     /// Return ring buffer item at position `idx`.
     ///
     /// Indeces greater than the size of the ring buffer will simply wrap around.
@@ -136,4 +146,31 @@ pub mod tests {
         assert!(rb.peek(1) == &5f32);
         assert!(rb.peek(2) == &6f32);
     }
+
+    #[test]
+    fn test_last() {
+        let mut rb = RingBuffer::<f32>::new(3);
+        assert!(rb.last().is_none());
+
+        rb.add(1_f32);
+        assert!(rb.last() == Some(&1f32));
+
+        rb.add(2_f32);
+        rb.add(3_f32);
+        rb.add(4_f32);
+        assert!(rb.last() == Some(&4f32));
+    }
+
+    #[test]
+    fn test_iter() {
+        let mut rb = RingBuffer::<f32>::new(3);
+
+        rb.add(1_f32);
+        rb.add(2_f32);
+        assert!(rb.iter().copied().collect::<Vec<_>>() == [1f32, 2f32]);
+
+        rb.add(3_f32);
+        rb.add(4_f32);
+        assert!(rb.iter().copied().collect::<Vec<_>>() == [2f32, 3f32, 4f32]);
+    }
 }